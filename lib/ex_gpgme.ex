@@ -32,6 +32,32 @@ defmodule ExGpgme do
     :loopback |
     {:other, integer}
 
+  @typedoc """
+  Key listing mode. Used as a list of modes.
+  """
+  @type key_list_mode :: :local |
+    :extern |
+    :sigs |
+    :sig_notations |
+    :with_secret |
+    :with_tofu |
+    :ephemeral |
+    :validate
+
+  @typedoc """
+  An operation or flag that depends on the version of the `gpgme` library or the installed engine.
+  See `ExGpgme.Context.capabilities/1`.
+  """
+  @type capability :: :pinentry_loopback |
+    :sender |
+    :session_key_export |
+    :override_session_key |
+    :quick_key_creation |
+    :tofu |
+    :locate_key |
+    :import_filter |
+    :no_symkey_cache
+
   @typedoc """
   This option is used to change the operation mode of the signing.
   """
@@ -40,6 +66,12 @@ defmodule ExGpgme do
     :clear |
     {:other, integer}
 
+  @typedoc """
+  Format of the audit log. See `ExGpgme.Context.audit_log/2`.
+  """
+  @type audit_log_format :: :text |
+    :html
+
   @typedoc """
   Signature Validity
   """
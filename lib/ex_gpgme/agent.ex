@@ -0,0 +1,183 @@
+defmodule ExGpgme.Agent do
+  @moduledoc """
+  Manages the passphrase cache of the `gpg-agent` of a context.
+
+  All functions talk to the agent of the home directory of the given context and start it if it is not running.
+  Every call with a regular context opens a new connection to the agent, connect once with `connect/1` and pass the
+  agent context to the functions when calling them repeatedly. Keys are identified by their keygrip, see the
+  `keygrip` of `ExGpgme.Keys.Subkey`.
+  """
+
+  alias ExGpgme.Context
+  alias ExGpgme.Agent.KeyInfo
+  alias ExGpgme.Results.AssuanResult
+
+  @doc """
+  Connects to the agent of the home directory of `context` and starts it if it is not running.
+
+  The agent context can be passed to all functions of this module instead of `context`. An agent context is returned
+  as it is.
+
+  ### Examples
+
+      iex> context = ExGpgme.Context.from_protocol!(:open_pgp)
+      iex> {:ok, agent} = ExGpgme.Agent.connect(context)
+      iex> ExGpgme.Agent.clear_passphrase(agent, "9C5AC4E4F0D5D24A0FD42C3FBB2AB8E0E3A2F1B7")
+      :ok
+
+  """
+  @spec connect(context :: Context.context) :: {:ok, Context.context} | {:error, String.t}
+  def connect(context) do
+    case Context.protocol(context) do
+      :assuan -> {:ok, context}
+      _protocol -> Context.agent_context(context)
+    end
+  end
+
+  @doc """
+  See `connect/1`.
+
+  """
+  @spec connect!(context :: Context.context) :: Context.context | no_return
+  def connect!(context) do
+    case connect(context) do
+      {:ok, result} -> result
+      {:error, error} -> raise error
+    end
+  end
+
+  @doc """
+  Caches the passphrase `passphrase` for the key with the keygrip `keygrip`.
+
+  The agent only accepts preset passphrases with the option `allow-preset-passphrase`, which can be enabled with
+  `ExGpgme.GpgConf.change_option(context, "gpg-agent", "allow-preset-passphrase", true)`.
+
+  ### Options
+
+  * `:timeout` - Seconds until the passphrase expires. Without timeout the passphrase stays cached until it is
+    cleared or the agent is stopped.
+
+  ### Examples
+
+      iex> context = ExGpgme.Context.from_protocol!(:open_pgp)
+      iex> ExGpgme.Agent.preset_passphrase(context, "9C5AC4E4F0D5D24A0FD42C3FBB2AB8E0E3A2F1B7", "secret")
+      :ok
+
+  """
+  @spec preset_passphrase(context :: Context.context, keygrip :: String.t, passphrase :: String.t,
+    options :: [timeout: pos_integer]) :: :ok | {:error, String.t}
+  def preset_passphrase(context, keygrip, passphrase, options \\ []) do
+    timeout = Keyword.get(options, :timeout, -1)
+
+    transact(context, "PRESET_PASSPHRASE #{keygrip} #{timeout} #{Base.encode16(passphrase)}")
+  end
+
+  @doc """
+  See `preset_passphrase/4`.
+
+  """
+  @spec preset_passphrase!(context :: Context.context, keygrip :: String.t, passphrase :: String.t,
+    options :: [timeout: pos_integer]) :: nil | no_return
+  def preset_passphrase!(context, keygrip, passphrase, options \\ []) do
+    case preset_passphrase(context, keygrip, passphrase, options) do
+      :ok -> nil
+      {:error, error} -> raise error
+    end
+  end
+
+  @doc """
+  Removes the cached passphrase of the key with the keygrip `keygrip`.
+
+  ### Examples
+
+      iex> context = ExGpgme.Context.from_protocol!(:open_pgp)
+      iex> ExGpgme.Agent.clear_passphrase(context, "9C5AC4E4F0D5D24A0FD42C3FBB2AB8E0E3A2F1B7")
+      :ok
+
+  """
+  @spec clear_passphrase(context :: Context.context, keygrip :: String.t) :: :ok | {:error, String.t}
+  def clear_passphrase(context, keygrip), do: transact(context, "CLEAR_PASSPHRASE --mode=normal #{keygrip}")
+
+  @doc """
+  See `clear_passphrase/2`.
+
+  """
+  @spec clear_passphrase!(context :: Context.context, keygrip :: String.t) :: nil | no_return
+  def clear_passphrase!(context, keygrip) do
+    case clear_passphrase(context, keygrip) do
+      :ok -> nil
+      {:error, error} -> raise error
+    end
+  end
+
+  @doc """
+  Lists the keys held by the agent with their cache state.
+
+  ### Examples
+
+      iex> context = ExGpgme.Context.from_protocol!(:open_pgp)
+      iex> ExGpgme.Agent.key_infos(context)
+      {:ok, [%ExGpgme.Agent.KeyInfo{keygrip: "9C5AC4E4F0D5D24A0FD42C3FBB2AB8E0E3A2F1B7", type: :disk,
+        cached: false, protection: :protected}]}
+
+  """
+  @spec key_infos(context :: Context.context) :: {:ok, [KeyInfo.t]} | {:error, String.t}
+  def key_infos(context) do
+    with {:ok, agent} <- connect(context),
+         {:ok, %AssuanResult{status: status}} <- Context.assuan_transact(agent, "KEYINFO --list"),
+         {:ok, key_infos} <- parse_key_infos(for({"KEYINFO", args} <- status, do: args)) do
+      {:ok, Enum.reverse(key_infos)}
+    end
+  end
+
+  @doc """
+  See `key_infos/1`.
+
+  """
+  @spec key_infos!(context :: Context.context) :: [KeyInfo.t] | no_return
+  def key_infos!(context) do
+    case key_infos(context) do
+      {:ok, result} -> result
+      {:error, error} -> raise error
+    end
+  end
+
+  defp transact(context, command) do
+    with {:ok, agent} <- connect(context),
+         {:ok, _result} <- Context.assuan_transact(agent, command) do
+      :ok
+    end
+  end
+
+  defp parse_key_infos(status_args) do
+    Enum.reduce_while(status_args, {:ok, []}, fn args, {:ok, key_infos} ->
+      case parse_key_info(args) do
+        {:ok, key_info} -> {:cont, {:ok, [key_info | key_infos]}}
+        {:error, error} -> {:halt, {:error, error}}
+      end
+    end)
+  end
+
+  defp parse_key_info(args) when is_binary(args) do
+    case String.split(args, " ") do
+      [keygrip, type, _serial_number, _id, cached, protection | _] ->
+        {:ok, %KeyInfo{
+          keygrip: keygrip,
+          type: parse_type(type),
+          cached: cached == "1",
+          protection: parse_protection(protection),
+        }}
+      _ ->
+        {:error, "Could not parse key info #{inspect args}"}
+    end
+  end
+  defp parse_key_info(args), do: {:error, "Could not parse key info #{inspect args}"}
+
+  defp parse_type("D"), do: :disk
+  defp parse_type("T"), do: :smartcard
+  defp parse_type(_type), do: :unknown
+
+  defp parse_protection("P"), do: :protected
+  defp parse_protection("C"), do: :unprotected
+  defp parse_protection(_protection), do: :unknown
+end
@@ -0,0 +1,29 @@
+defmodule ExGpgme.Agent.KeyInfo do
+  @moduledoc """
+  Key held by `gpg-agent`.
+
+  * `keygrip` - Keygrip of the key. See `ExGpgme.Keys.Subkey`.
+  * `type` - `:disk` for keys on disk, `:smartcard` for keys on a smartcard.
+  * `cached` - `true` if the passphrase of the key is cached.
+  * `protection` - `:protected` if the key is protected by a passphrase.
+  """
+
+  @type type :: :disk | :smartcard | :unknown
+
+  @type protection :: :protected | :unprotected | :unknown
+
+  @type t :: %__MODULE__{
+    keygrip: String.t,
+    type: type,
+    cached: boolean,
+    protection: protection,
+  }
+
+  @enforce_keys [
+    :keygrip,
+    :type,
+    :cached,
+    :protection,
+  ]
+  defstruct @enforce_keys
+end
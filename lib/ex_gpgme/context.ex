@@ -4,13 +4,25 @@ defmodule ExGpgme.Context do
 
   **Warning**: The context is not immutable.
   Therefore multiple processes will influence each other with configuration, flags etc.
+
+  A context can hold a pool of identically configured engine contexts (see the `:pool_size` option of
+  `from_protocol/2`). Every operation checks out one of them, so concurrent callers do not wait for each other and
+  never share engine state during an operation. Settings are applied to every context of the pool: a setting rejected
+  by the first of them is not applied at all, any other context that rejects it is replaced by a copy of the first.
+  If that copy fails too, the context is removed from the pool and the setting returns an error.
+
+  Every engine context is owned by its own native thread. All functions of this module send their work to that
+  thread, so an engine context is never used by two threads at once.
   """
 
   use Rustler, otp_app: :ex_gpgme, crate: :exgpgme
 
-  alias ExGpgme.Results.{ImportResult, VerificationResult}
-  alias ExGpgme.Keys.Key
+  alias ExGpgme.Results.{AssuanResult, DecryptionResult, ImportResult, VerificationResult}
+  alias ExGpgme.Keys.{Key, KeyDetails}
   alias ExGpgme.EncryptFlags
+  alias ExGpgme.ImportOptions
+  alias ExGpgme.ExportMode
+  alias ExGpgme.Notation.SignatureNotation
   alias ExGpgme.Engine.EngineInfo
 
   @typedoc """
@@ -21,11 +33,47 @@ defmodule ExGpgme.Context do
   """
   @opaque context :: reference
 
+  @typedoc """
+  Option for `from_protocol/2`
+
+  * `:armor` - See `set_armor/2`.
+  * `:text_mode` - See `set_text_mode/2`.
+  * `:offline` - See `set_offline/2`.
+  * `:include_certs` - See `set_include_certs/2`.
+  * `:engine_path` - See `set_engine_path/2`.
+  * `:engine_home_dir` - See `set_engine_home_dir/2`.
+  * `:pinentry_mode` - See `set_pinentry_mode/2`.
+  * `:flags` - List of flag names and values. See `set_flag/3`.
+  * `:key_list_mode` - See `set_key_list_mode/2`.
+  * `:signers` - See `add_signer/2`.
+  * `:sender` - See `set_sender/2`.
+  * `:keyserver` - See `set_keyserver/2`. Needs `:engine_home_dir` or `ephemeral/2`.
+  * `:pool_size` - Number of engine contexts operations are spread over. Defaults to `1`.
+  * `:kill_on_drop` - See `set_kill_on_drop/2`.
+  """
+  @type option :: {:armor, boolean} |
+    {:text_mode, boolean} |
+    {:offline, boolean} |
+    {:include_certs, integer} |
+    {:engine_path, String.t} |
+    {:engine_home_dir, String.t} |
+    {:pinentry_mode, ExGpgme.pinentry_mode} |
+    {:flags, [{String.t, String.t}]} |
+    {:key_list_mode, [ExGpgme.key_list_mode]} |
+    {:signers, [Key.t]} |
+    {:sender, String.t} |
+    {:keyserver, String.t} |
+    {:pool_size, pos_integer} |
+    {:kill_on_drop, boolean}
+
   @doc """
   The function creates a context with the protocol. All crypto operations will be performed by the crypto engine
   configured for that protocol.
   See [Protocols and Engines](https://www.gnupg.org/documentation/manuals/gpgme/Protocols-and-Engines.html#Protocols-and-Engines).
 
+  The `options` are applied before the context is returned, so no other process can observe a partly configured
+  context. An unknown or invalid option returns an error.
+
   Setting the protocol does not check if the crypto engine for that protocol is available and installed correctly.
   See [Engine Version Check](https://www.gnupg.org/documentation/manuals/gpgme/Engine-Version-Check.html#Engine-Version-Check).
 
@@ -34,17 +82,140 @@ defmodule ExGpgme.Context do
       iex> ExGpgme.Context.from_protocol(:open_pgp)
       {:ok, #Reference<0.1689386418.123076612.191614>}
 
+      iex> ExGpgme.Context.from_protocol(:open_pgp, armor: true, pinentry_mode: :loopback,
+      ...>   flags: [{"raw-description", "1"}])
+      {:ok, #Reference<0.1689386418.123076612.191614>}
+
+      iex> ExGpgme.Context.from_protocol(:open_pgp, pool_size: System.schedulers_online)
+      {:ok, #Reference<0.1689386418.123076612.191614>}
+
+      iex> ExGpgme.Context.from_protocol(:open_pgp, foo: true)
+      {:error, "Unknown option foo"}
+
+  """
+  @spec from_protocol(protocol :: ExGpgme.protocol, options :: [option]) :: {:ok, context} | {:error, String.t}
+  def from_protocol(_protocol, _options \\ []), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  See `from_protocol/2`.
+
+  """
+  @spec from_protocol!(protocol :: ExGpgme.protocol, options :: [option]) :: context | no_return
+  def from_protocol!(protocol, options \\ []) do
+    case from_protocol(protocol, options) do
+      {:ok, result} -> result
+      {:error, error} -> raise error
+    end
+  end
+
+  @doc """
+  Creates a context with a fresh temporary home dir, so it starts with an empty key ring that is not shared with
+  any other context.
+
+  The home dir is only accessible by the current user and its `gpg-agent` allows the loopback pinentry. The pinentry
+  mode of the context is `:loopback`. The `options` are the same as for `from_protocol/2`.
+
+  When the context (and every clone of it, see `clone/1`) is garbage collected, all GnuPG components of the home dir
+  are killed and the home dir is deleted.
+
+  ### Examples
+
+      iex> ExGpgme.Context.ephemeral(:open_pgp)
+      {:ok, #Reference<0.1689386418.123076612.191614>}
+
+      iex> ExGpgme.Context.ephemeral(:open_pgp, armor: true)
+      {:ok, #Reference<0.1689386418.123076612.191614>}
+
+  """
+  @spec ephemeral(protocol :: ExGpgme.protocol, options :: [option]) :: {:ok, context} | {:error, String.t}
+  def ephemeral(_protocol, _options \\ []), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  See `ephemeral/2`.
+
+  """
+  @spec ephemeral!(protocol :: ExGpgme.protocol, options :: [option]) :: context | no_return
+  def ephemeral!(protocol, options \\ []) do
+    case ephemeral(protocol, options) do
+      {:ok, result} -> result
+      {:error, error} -> raise error
+    end
+  end
+
+  @typedoc """
+  Snapshot of the settings of a context. See `settings/1`.
+  """
+  @type settings :: %{
+    protocol: ExGpgme.protocol,
+    armor: boolean,
+    text_mode: boolean,
+    offline: boolean,
+    include_certs: integer,
+    pinentry_mode: ExGpgme.pinentry_mode,
+    engine_path: String.t | nil,
+    engine_home_dir: String.t | nil,
+    flags: [{String.t, String.t}],
+    key_list_mode: [ExGpgme.key_list_mode],
+    signers: [Key.t],
+    sender: String.t | nil,
+    keyserver: String.t | nil,
+    pool_size: pos_integer,
+    kill_on_drop: boolean,
+  }
+
+  @doc """
+  Returns a snapshot of the current settings of the context. Only flags with a value are listed.
+
+  ### Examples
+
+      iex> :open_pgp
+      ...> |> ExGpgme.Context.from_protocol!
+      ...> |> ExGpgme.Context.settings
+      {:ok,
+       %{armor: false, engine_home_dir: nil, engine_path: "/usr/bin/gpg", flags: [], include_certs: -256,
+         key_list_mode: [:local], keyserver: nil, kill_on_drop: false, offline: false, pinentry_mode: :default,
+         pool_size: 1, protocol: :open_pgp, sender: nil, signers: [], text_mode: false}}
+
+  """
+  @spec settings(context :: context) :: {:ok, settings} | {:error, String.t}
+  def settings(_context), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  See `settings/1`.
+
+  """
+  @spec settings!(context :: context) :: settings | no_return
+  def settings!(context) do
+    case settings(context) do
+      {:ok, result} -> result
+      {:error, error} -> raise error
+    end
+  end
+
+  @doc """
+  Creates a new independent context with the settings of `context`. The signature notations and the signature policy
+  URL are copied as well. The clone has the same pool size as `context`.
+
+  Use it to give every process its own copy of a configured context.
+
+  ### Examples
+
+      iex> :open_pgp
+      ...> |> ExGpgme.Context.from_protocol!(armor: true)
+      ...> |> ExGpgme.Context.clone
+      {:ok, #Reference<0.1689386418.123076612.191614>}
+
   """
-  @spec from_protocol(protocol :: ExGpgme.protocol) :: {:ok, context} | {:error, String.t}
-  def from_protocol(_protocol), do: :erlang.nif_error(:nif_not_loaded)
+  @spec clone(context :: context) :: {:ok, context} | {:error, String.t}
+  def clone(_context), do: :erlang.nif_error(:nif_not_loaded)
 
   @doc """
-  See `from_protocol/1`.
+  See `clone/1`.
 
   """
-  @spec from_protocol!(protocol :: ExGpgme.protocol) :: context | no_return
-  def from_protocol!(protocol) do
-    case from_protocol(protocol) do
+  @spec clone!(context :: context) :: context | no_return
+  def clone!(context) do
+    case clone(context) do
       {:ok, result} -> result
       {:error, error} -> raise error
     end
@@ -167,6 +338,43 @@ defmodule ExGpgme.Context do
   @spec set_offline(context :: context, yes :: boolean):: :ok
   def set_offline(_context, _yes), do: :erlang.nif_error(:nif_not_loaded)
 
+  @doc """
+  The function returns the number of certificates to include in an S/MIME message. See `set_include_certs/2`.
+
+  ### Examples
+
+      iex> :cms
+      ...> |> ExGpgme.Context.from_protocol!
+      ...> |> ExGpgme.Context.include_certs
+      -256
+
+  """
+  @spec include_certs(context :: context):: integer
+  def include_certs(_context), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  The function specifies how many certificates should be included in an S/MIME signed message. By default, the
+  default of the crypto engine is used (`-256`).
+
+  * `-2` - Include all certificates except the root certificate.
+  * `-1` - Include all certificates.
+  * `0` - Include no certificates.
+  * `1` - Include only the sender's certificate.
+  * `n` - Include the first `n` certificates of the certificate chain, starting with the sender's certificate.
+
+  This option is only relevant to the CMS crypto engine, and ignored by all other engines.
+
+  ### Examples
+
+      iex> :cms
+      ...> |> ExGpgme.Context.from_protocol!
+      ...> |> ExGpgme.Context.set_include_certs(1)
+      :ok
+
+  """
+  @spec set_include_certs(context :: context, count :: integer):: :ok
+  def set_include_certs(_context, _count), do: :erlang.nif_error(:nif_not_loaded)
+
   @doc """
   The value of flags settable by `set_flag/3` can be retrieved by this function. If name is unknown the function returns
   `:error`. For boolean flags an empty string is returned for `false` and the string `"1"` is returned for `true`;
@@ -264,6 +472,125 @@ defmodule ExGpgme.Context do
     end
   end
 
+  @doc """
+  Sends the command `command` to an Assuan server like `gpg-agent` and collects the answer.
+
+  The context has to use the protocol `:assuan`. The engine path of the context is the socket of the server; by
+  default it is the socket of `gpg-agent` of the default home directory. Use `set_engine_path/2` with the output of
+  `gpgconf --list-dirs agent-socket` for other home directories.
+
+  Inquiries of the server are answered from `inquiry_answers`, which maps the inquiry name to the answer. An inquiry
+  without answer cancels the command.
+
+  ### Examples
+
+      iex> :assuan
+      ...> |> ExGpgme.Context.from_protocol!
+      ...> |> ExGpgme.Context.assuan_transact("GETINFO version")
+      {:ok, %ExGpgme.Results.AssuanResult{data: "2.2.0", status: [], inquiries: []}}
+
+      iex> :assuan
+      ...> |> ExGpgme.Context.from_protocol!
+      ...> |> ExGpgme.Context.assuan_transact("GENKEY --no-protection",
+      ...>   %{"KEYPARAM" => "(genkey(ecc(curve 7:Ed25519)(flags eddsa)))"})
+      {:ok, %ExGpgme.Results.AssuanResult{data: "(10:public-key(3:ecc...", status: [{"KEY_CREATED", ...}, ...],
+        inquiries: [{"KEYPARAM", nil}]}}
+
+  """
+  @spec assuan_transact(context :: context, command :: String.t, inquiry_answers :: %{String.t => binary})
+    :: {:ok, AssuanResult.t} | {:error, String.t}
+  def assuan_transact(context, command, inquiry_answers \\ %{}),
+    do: assuan_transact_with_inquiries(context, command, Enum.to_list(inquiry_answers))
+
+  @spec assuan_transact_with_inquiries(context :: context, command :: String.t, [{String.t, binary}])
+    :: {:ok, AssuanResult.t} | {:error, String.t}
+  defp assuan_transact_with_inquiries(_context, _command, _inquiry_answers), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  See `assuan_transact/3`.
+
+  """
+  @spec assuan_transact!(context :: context, command :: String.t, inquiry_answers :: %{String.t => binary})
+    :: AssuanResult.t | no_return
+  def assuan_transact!(context, command, inquiry_answers \\ %{}) do
+    case assuan_transact(context, command, inquiry_answers) do
+      {:ok, result} -> result
+      {:error, error} -> raise error
+    end
+  end
+
+  @doc false
+  @spec agent_context(context :: context) :: {:ok, context} | {:error, String.t}
+  def agent_context(_context), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc false
+  @spec gpgconf_control(context :: context, action :: :launch | :reload | :kill, component :: String.t)
+    :: :ok | {:error, String.t}
+  def gpgconf_control(_context, _action, _component), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc false
+  @spec gpgconf_components(context :: context) :: {:ok, [ExGpgme.GpgConf.Component.t]} | {:error, String.t}
+  def gpgconf_components(_context), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc false
+  @spec gpgconf_change_option(context :: context, component :: String.t, option :: String.t,
+    value :: ExGpgme.GpgConf.value) :: :ok | {:error, String.t}
+  def gpgconf_change_option(_context, _component, _option, _value), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc false
+  @spec library_version :: String.t
+  def library_version, do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc false
+  @spec compare_versions(version :: String.t, other_version :: String.t) :: {:ok, :lt | :eq | :gt} | {:error, String.t}
+  def compare_versions(_version, _other_version), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc false
+  @spec engine_infos :: {:ok, [EngineInfo.t]} | {:error, String.t}
+  def engine_infos, do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Reports which operations and flags are supported by the `gpgme` library and the engine of the context.
+
+  The support is derived from the library version and the version of the engine of the context's protocol, see
+  `engine_info/1`.
+
+  * `:pinentry_loopback` - Passphrase functions like `decrypt_with_passphrase/3`.
+  * `:sender` - `set_sender/2`.
+  * `:session_key_export` - `decrypt_export_session_key/2`.
+  * `:override_session_key` - `decrypt_with_session_key/3`.
+  * `:quick_key_creation` - The quick key creation commands of the engine, like `--quick-gen-key` of `gpg`.
+  * `:tofu` - The `:with_tofu` key list mode, see `set_key_list_mode/2`.
+  * `:locate_key` - `locate_key/2`.
+  * `:import_filter` - Filters of `import/3`.
+  * `:no_symkey_cache` - `encrypt_symmetric/5` and `decrypt_with_passphrase/3` without caching the passphrase.
+
+  ### Examples
+
+      iex> :open_pgp
+      ...> |> ExGpgme.Context.from_protocol!
+      ...> |> ExGpgme.Context.capabilities
+      %{import_filter: false, locate_key: false, no_symkey_cache: false, override_session_key: true,
+        pinentry_loopback: true, quick_key_creation: true, sender: true, session_key_export: true, tofu: true}
+
+  """
+  @spec capabilities(context :: context) :: %{ExGpgme.capability => boolean}
+  def capabilities(_context), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Checks if the context supports `capability`. See `capabilities/1`.
+
+  ### Examples
+
+      iex> :open_pgp
+      ...> |> ExGpgme.Context.from_protocol!
+      ...> |> ExGpgme.Context.supports?(:pinentry_loopback)
+      true
+
+  """
+  @spec supports?(context :: context, capability :: ExGpgme.capability) :: boolean
+  def supports?(context, capability), do: Map.get(capabilities(context), capability, false)
+
   @doc """
   Set engine path for GPG Context.
 
@@ -317,215 +644,799 @@ defmodule ExGpgme.Context do
   end
 
   @doc """
-  The function returns the mode set for the context.
+  The function returns the key listing mode of the context.
 
   ### Examples
 
       iex> :open_pgp
       ...> |> ExGpgme.Context.from_protocol!
-      ...> |> ExGpgme.Context.pinentry_mode
-      :default
+      ...> |> ExGpgme.Context.key_list_mode
+      [:local]
 
   """
-  @spec pinentry_mode(context :: context):: ExGpgme.pinentry_mode
-  def pinentry_mode(_context), do: :erlang.nif_error(:nif_not_loaded)
+  @spec key_list_mode(context :: context) :: [ExGpgme.key_list_mode]
+  def key_list_mode(_context), do: :erlang.nif_error(:nif_not_loaded)
 
   @doc """
-  The function sets the mode for the context.
+  The function sets the key listing mode of the context. It is used by key lookups like `find_key/2`.
 
   ### Examples
 
       iex> :open_pgp
       ...> |> ExGpgme.Context.from_protocol!
-      ...> |> ExGpgme.Context.set_pinentry_mode(:ask)
+      ...> |> ExGpgme.Context.set_key_list_mode([:local, :sigs])
       :ok
 
   """
-  @spec set_pinentry_mode(context :: context, mode :: ExGpgme.pinentry_mode):: :ok | {:error, String.t}
-  def set_pinentry_mode(_context, _mode), do: :erlang.nif_error(:nif_not_loaded)
+  @spec set_key_list_mode(context :: context, mode :: [ExGpgme.key_list_mode]) :: :ok | {:error, String.t}
+  def set_key_list_mode(_context, _mode), do: :erlang.nif_error(:nif_not_loaded)
 
   @doc """
-  See `set_pinentry_mode/2`
+  See `set_key_list_mode/2`
 
   """
-  @spec set_pinentry_mode!(context :: context, mode :: ExGpgme.pinentry_mode):: nil | no_return
-  def set_pinentry_mode!(context, mode) do
-    case set_pinentry_mode(context, mode) do
+  @spec set_key_list_mode!(context :: context, mode :: [ExGpgme.key_list_mode]) :: nil | no_return
+  def set_key_list_mode!(context, mode) do
+    case set_key_list_mode(context, mode) do
       :ok -> nil
       {:error, error} -> raise error
     end
   end
 
   @doc """
-  Import Keys
+  The function returns the keys used for signing with the context.
 
   ### Examples
 
       iex> :open_pgp
       ...> |> ExGpgme.Context.from_protocol!
-      ...> |> ExGpgme.Context.import(File.read!("priv/test/keys/sender_public.asc"))
-      {:ok,
-       %ExGpgme.Results.ImportResult{considered: 1, imported: 0,
-        imported_rsa: 0,
-        imports: [%ExGpgme.Results.Import{fingerprint: "95E93F470BCB2E96C648572DFBFA85913EE05E95"}],
-        new_revocations: 0, new_signatures: 0, new_subkeys: 0,
-        new_user_ids: 0, not_imported: 0, secret_considered: 0,
-        secret_imported: 0, secret_unchanged: 0, unchanged: 1,
-        without_user_id: 0}}
-
-  """
-  @spec import(context :: context, data :: String.t) :: {:ok, ImportResult.t} | {:error, String.t}
-  def import(_context, _data), do: :erlang.nif_error(:nif_not_loaded)
-
-  @doc """
-  See `import/2`.
+      ...> |> ExGpgme.Context.signers
+      []
 
   """
-  @spec import!(context :: context, data :: String.t) :: ImportResult.t | no_return
-  def import!(context, data) do
-    case __MODULE__.import(context, data) do
-      {:ok, result} -> result
-      {:error, error} -> raise error
-    end
-  end
+  @spec signers(context :: context) :: [Key.t]
+  def signers(_context), do: :erlang.nif_error(:nif_not_loaded)
 
   @doc """
-  Find a key by Fingerprint
+  The function adds the key `key` to the keys used for signing with the context. Without signers the default key of
+  the engine is used.
 
   ### Examples
 
       iex> context = ExGpgme.Context.from_protocol!(:open_pgp)
-      iex> ExGpgme.Context.import!(context, File.read!("priv/test/keys/sender_public.asc"))
-      iex> ExGpgme.Context.find_key(context, "95E93F470BCB2E96C648572DFBFA85913EE05E95")
-      {:ok, #Reference<0.411470915.3086352388.254522>}
-
-      iex> context = ExGpgme.Context.from_protocol!(:open_pgp)
-      iex> ExGpgme.Context.import!(context, File.read!("priv/test/keys/sender_public.asc"))
-      iex> ExGpgme.Context.find_key(context, "not-existing-fingerprint")
-      {:error, "End of file"}
+      iex> ExGpgme.Context.import!(context, File.read!("priv/test/keys/sender_secret.asc"))
+      iex> key = ExGpgme.Context.find_key!(context, "95E93F470BCB2E96C648572DFBFA85913EE05E95")
+      iex> ExGpgme.Context.add_signer(context, key)
+      :ok
 
   """
-  @spec find_key(context :: context, fingerprint :: String.t) :: {:ok, Key.t} | {:error, String.t}
-  def find_key(_context, _fingerprint), do: :erlang.nif_error(:nif_not_loaded)
+  @spec add_signer(context :: context, key :: Key.t) :: :ok | {:error, String.t}
+  def add_signer(_context, _key), do: :erlang.nif_error(:nif_not_loaded)
 
   @doc """
-  See `find_key/2`.
+  See `add_signer/2`
 
   """
-  @spec find_key!(context :: context, fingerprint :: String.t) :: Key.t | no_return
-  def find_key!(context, fingerprint) do
-    case find_key(context, fingerprint) do
-      {:ok, result} -> result
+  @spec add_signer!(context :: context, key :: Key.t) :: nil | no_return
+  def add_signer!(context, key) do
+    case add_signer(context, key) do
+      :ok -> nil
       {:error, error} -> raise error
     end
   end
 
   @doc """
-  Encrypts a message for the specified recipients.
+  The function removes all signers from the context.
 
   ### Examples
 
-      iex> context = ExGpgme.Context.from_protocol!(:open_pgp)
-      iex> ExGpgme.Context.set_armor(context, true)
-      iex> ExGpgme.Context.import!(context, File.read!("priv/test/keys/sender_public.asc"))
-      iex> recipient = ExGpgme.Context.find_key!(context, "95E93F470BCB2E96C648572DFBFA85913EE05E95")
-      iex> ExGpgme.Context.encrypt(context, [recipient], "Hello World", [:always_trust])
-      {:ok,
-       "-----BEGIN PGP MESSAGE-----\\n[data]\\n-----END PGP MESSAGE-----\\n"}
+      iex> :open_pgp
+      ...> |> ExGpgme.Context.from_protocol!
+      ...> |> ExGpgme.Context.clear_signers
+      :ok
 
   """
-  @spec encrypt(context :: context, recipients :: [Key.t], data :: String.t, flags:: EncryptFlags.flags)
-    :: {:ok, String.t} | {:error, String.t}
-  def encrypt(context, recipients, data, flags \\ []),
-    do: encrypt_with_flags(context, recipients, data, flags)
-
-  @spec encrypt_with_flags(context :: context, recipients :: [Key.t], data :: String.t, flags:: EncryptFlags.flags)
-    :: {:ok, String.t} | {:error, String.t}
-  defp encrypt_with_flags(_context, _recipients, _data, _flags), do: :erlang.nif_error(:nif_not_loaded)
+  @spec clear_signers(context :: context) :: :ok
+  def clear_signers(_context), do: :erlang.nif_error(:nif_not_loaded)
 
   @doc """
-  See `encrypt/4`
-  """
-  @spec encrypt!(context :: context, recipients :: [Key.t], data :: String.t, flags:: EncryptFlags.flags)
-    :: String.t | no_return
-  def encrypt!(context, recipients, data, flags \\ []) do
-    case encrypt(context, recipients, data, flags) do
-      {:ok, cypthertext} -> cypthertext
-      {:error, error} -> raise error
-    end
-  end
+  Kill all GnuPG components (`gpg-agent`, `dirmngr`, `keyboxd`, ...) of the engine home dir when the context is
+  garbage collected. Nothing is killed if no engine home dir is set, so the daemons of the user are never touched.
 
-  @doc """
-  Signs and encrypts a message for the specified recipients.
+  Useful for test suites and short lived jobs with a private home dir. See `ExGpgme.GpgConf.kill/2` to stop them
+  explicitly.
 
   ### Examples
 
-      iex> context = ExGpgme.Context.from_protocol!(:open_pgp)
-      iex> ExGpgme.Context.set_armor(context, true)
-      iex> ExGpgme.Context.import!(context, File.read!("priv/test/keys/sender_public.asc"))
-      iex> recipient = ExGpgme.Context.find_key!(context, "95E93F470BCB2E96C648572DFBFA85913EE05E95")
-      iex> ExGpgme.Context.sign_and_encrypt(context, [recipient], "Hello World", [:always_trust])
-      {:ok, "-----BEGIN PGP MESSAGE-----\\n[data]\\n-----END PGP MESSAGE-----\\n"}
+      iex> :open_pgp
+      ...> |> ExGpgme.Context.from_protocol!(engine_home_dir: "/tmp/job")
+      ...> |> ExGpgme.Context.set_kill_on_drop(true)
+      :ok
 
   """
-  @spec sign_and_encrypt(context :: context, recipients :: [Key.t], data :: String.t, flags:: EncryptFlags.flags)
-    :: {:ok, String.t} | {:error, String.t}
-  def sign_and_encrypt(context, recipients, data, flags \\ []),
-    do: sign_and_encrypt_with_flags(context, recipients, data, flags)
-
-  @spec sign_and_encrypt_with_flags(context :: context, recipients :: [Key.t],
-    data :: String.t, flags:: EncryptFlags.flags)
-    :: {:ok, String.t} | {:error, String.t}
-  defp sign_and_encrypt_with_flags(_context, _recipients, _data, _flags), do: :erlang.nif_error(:nif_not_loaded)
+  @spec set_kill_on_drop(context :: context, yes :: boolean) :: :ok
+  def set_kill_on_drop(_context, _yes), do: :erlang.nif_error(:nif_not_loaded)
 
   @doc """
-  See `sign_and_encrypt/4`
+  Returns the keyserver set with `set_keyserver/2` or `nil` if none was set.
+
+  ### Examples
+
+      iex> :open_pgp
+      ...> |> ExGpgme.Context.from_protocol!
+      ...> |> ExGpgme.Context.keyserver
+      nil
+
   """
-  @spec sign_and_encrypt!(context :: context, recipients :: [Key.t], data :: String.t, flags:: EncryptFlags.flags)
-    :: String.t | no_return
-  def sign_and_encrypt!(context, recipients, data, flags \\ []) do
-    case sign_and_encrypt(context, recipients, data, flags) do
-      {:ok, cypthertext} -> cypthertext
-      {:error, error} -> raise error
-    end
-  end
+  @spec keyserver(context :: context) :: String.t | nil
+  def keyserver(_context), do: :erlang.nif_error(:nif_not_loaded)
 
   @doc """
-  The function deletes the key `key` from the key ring of the crypto engine used by `context`.
+  Set the keyserver used by `receive_keys/2` and `send_keys/2` of this context.
+
+  Keyserver operations run through `dirmngr`, which only reads the keyserver from its configuration. Therefore a
+  keyserver can only be set for contexts with an explicit home dir, see `set_engine_home_dir/2` and `ephemeral/2`.
+  For each keyserver operation it is written to the `dirmngr` configuration of that home dir and the previous value is
+  restored afterwards. Keyserver operations of contexts sharing a home dir run one after another.
 
   ### Examples
 
-      iex> context = ExGpgme.Context.from_protocol!(:open_pgp)
-      iex> ExGpgme.Context.import!(context, File.read!("priv/test/keys/sender_public.asc"))
-      iex> key = ExGpgme.Context.find_key!(context, "95E93F470BCB2E96C648572DFBFA85913EE05E95")
-      iex> ExGpgme.Context.delete_key(context, key)
+      iex> :open_pgp
+      ...> |> ExGpgme.Context.ephemeral!
+      ...> |> ExGpgme.Context.set_keyserver("hkp://127.0.0.1:11371")
       :ok
 
   """
-  @spec delete_key(context :: context, key :: Key.t) :: :ok | {:error, String.t}
-  def delete_key(_context, _key), do: :erlang.nif_error(:nif_not_loaded)
+  @spec set_keyserver(context :: context, keyserver :: String.t) :: :ok | {:error, String.t}
+  def set_keyserver(_context, _keyserver), do: :erlang.nif_error(:nif_not_loaded)
 
   @doc """
-  See `delete_key/2`
+  See `set_keyserver/2`.
 
   """
-  @spec delete_key!(context :: context, key :: Key.t) :: nil | no_return
-  def delete_key!(context, key) do
-    case delete_key(context, key) do
+  @spec set_keyserver!(context :: context, keyserver :: String.t) :: nil | no_return
+  def set_keyserver!(context, keyserver) do
+    case set_keyserver(context, keyserver) do
       :ok -> nil
       {:error, error} -> raise error
     end
   end
 
   @doc """
-  The function deletes the secret key `key` from the key ring of the crypto engine used by `context`.
+  Returns the sender mailbox set with `set_sender/2` or `nil` if none was set.
 
   ### Examples
 
-      iex> context = ExGpgme.Context.from_protocol!(:open_pgp)
-      iex> ExGpgme.Context.import!(context, File.read!("priv/test/keys/sender_public.asc"))
-      iex> key = ExGpgme.Context.find_key!(context, "95E93F470BCB2E96C648572DFBFA85913EE05E95")
-      iex> ExGpgme.Context.delete_secret_key(context, key)
+      iex> :open_pgp
+      ...> |> ExGpgme.Context.from_protocol!
+      ...> |> ExGpgme.Context.sender
+      nil
+
+  """
+  @spec sender(context :: context) :: String.t | nil
+  def sender(_context), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Sets the sender mailbox of the context. `nil` removes it.
+
+  When signing with `sign/3` or `sign_and_encrypt/4`, the sender selects the signing key and is added as the Signer's
+  User ID to the signature. When verifying with `verify_opaque/3`, the signature is checked to match the sender.
+
+  ### Examples
+
+      iex> :open_pgp
+      ...> |> ExGpgme.Context.from_protocol!
+      ...> |> ExGpgme.Context.set_sender("alice@example.com")
+      :ok
+
+  """
+  @spec set_sender(context :: context, sender :: String.t | nil) :: :ok | {:error, String.t}
+  def set_sender(_context, _sender), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  See `set_sender/2`
+
+  """
+  @spec set_sender!(context :: context, sender :: String.t | nil) :: nil | no_return
+  def set_sender!(context, sender) do
+    case set_sender(context, sender) do
+      :ok -> nil
+      {:error, error} -> raise error
+    end
+  end
+
+  @doc """
+  The function returns the mode set for the context.
+
+  ### Examples
+
+      iex> :open_pgp
+      ...> |> ExGpgme.Context.from_protocol!
+      ...> |> ExGpgme.Context.pinentry_mode
+      :default
+
+  """
+  @spec pinentry_mode(context :: context):: ExGpgme.pinentry_mode
+  def pinentry_mode(_context), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  The function sets the mode for the context.
+
+  ### Examples
+
+      iex> :open_pgp
+      ...> |> ExGpgme.Context.from_protocol!
+      ...> |> ExGpgme.Context.set_pinentry_mode(:ask)
+      :ok
+
+  """
+  @spec set_pinentry_mode(context :: context, mode :: ExGpgme.pinentry_mode):: :ok | {:error, String.t}
+  def set_pinentry_mode(_context, _mode), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  See `set_pinentry_mode/2`
+
+  """
+  @spec set_pinentry_mode!(context :: context, mode :: ExGpgme.pinentry_mode):: nil | no_return
+  def set_pinentry_mode!(context, mode) do
+    case set_pinentry_mode(context, mode) do
+      :ok -> nil
+      {:error, error} -> raise error
+    end
+  end
+
+  @doc """
+  Adds a signature notation to the context. All signatures created with the context afterwards carry the notation,
+  for example the ones of `sign/3` and `sign_and_encrypt/4`.
+
+  `flags` may contain `:human_readable` if the `value` is readable text and `:critical` if the notation is critical.
+
+  ### Examples
+
+      iex> :open_pgp
+      ...> |> ExGpgme.Context.from_protocol!
+      ...> |> ExGpgme.Context.add_signature_notation("build@example.com", "1.2.3")
+      :ok
+
+  """
+  @spec add_signature_notation(context :: context, name :: String.t, value :: String.t,
+    flags :: [SignatureNotation.flag])
+    :: :ok | {:error, String.t}
+  def add_signature_notation(_context, _name, _value, _flags \\ [:human_readable]),
+    do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  See `add_signature_notation/4`
+
+  """
+  @spec add_signature_notation!(context :: context, name :: String.t, value :: String.t,
+    flags :: [SignatureNotation.flag])
+    :: nil | no_return
+  def add_signature_notation!(context, name, value, flags \\ [:human_readable]) do
+    case add_signature_notation(context, name, value, flags) do
+      :ok -> nil
+      {:error, error} -> raise error
+    end
+  end
+
+  @doc """
+  Removes all signature notations and the signature policy URL from the context.
+
+  ### Examples
+
+      iex> :open_pgp
+      ...> |> ExGpgme.Context.from_protocol!
+      ...> |> ExGpgme.Context.clear_signature_notations
+      :ok
+
+  """
+  @spec clear_signature_notations(context :: context) :: :ok
+  def clear_signature_notations(_context), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Returns the signature notations added with `add_signature_notation/4`.
+
+  ### Examples
+
+      iex> context = ExGpgme.Context.from_protocol!(:open_pgp)
+      iex> ExGpgme.Context.add_signature_notation(context, "build@example.com", "1.2.3")
+      iex> ExGpgme.Context.signature_notations(context)
+      {:ok, [%ExGpgme.Notation.SignatureNotation{is_critical: false, is_human_readable: true,
+        name: "build@example.com", value: "1.2.3"}]}
+
+  """
+  @spec signature_notations(context :: context) :: {:ok, [SignatureNotation.t]} | {:error, String.t}
+  def signature_notations(_context), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Sets the signature policy URL of the context. All signatures created with the context afterwards carry it.
+
+  ### Examples
+
+      iex> :open_pgp
+      ...> |> ExGpgme.Context.from_protocol!
+      ...> |> ExGpgme.Context.set_signature_policy_url("https://example.com/policy")
+      :ok
+
+  """
+  @spec set_signature_policy_url(context :: context, url :: String.t, critical :: boolean) :: :ok | {:error, String.t}
+  def set_signature_policy_url(_context, _url, _critical \\ false), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  See `set_signature_policy_url/3`
+
+  """
+  @spec set_signature_policy_url!(context :: context, url :: String.t, critical :: boolean) :: nil | no_return
+  def set_signature_policy_url!(context, url, critical \\ false) do
+    case set_signature_policy_url(context, url, critical) do
+      :ok -> nil
+      {:error, error} -> raise error
+    end
+  end
+
+  @doc """
+  Returns the signature policy URL set with `set_signature_policy_url/3` or `nil` if none was set.
+
+  ### Examples
+
+      iex> :open_pgp
+      ...> |> ExGpgme.Context.from_protocol!
+      ...> |> ExGpgme.Context.signature_policy_url
+      nil
+
+  """
+  @spec signature_policy_url(context :: context) :: String.t | nil
+  def signature_policy_url(_context), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Import Keys
+
+  The `options` are applied for this import only. See `ExGpgme.ImportOptions` for the available options and filters.
+  They need a `gpgme` version that supports the `"import-options"` and `"import-filter"` context flags.
+
+  With the `:cms` protocol, `data` may be a PKCS#12 bundle. Pass its passphrase as `{:passphrase, passphrase}` option.
+
+  ### Examples
+
+      iex> :open_pgp
+      ...> |> ExGpgme.Context.from_protocol!
+      ...> |> ExGpgme.Context.import(File.read!("priv/test/keys/sender_public.asc"))
+      {:ok,
+       %ExGpgme.Results.ImportResult{considered: 1, imported: 0,
+        imported_rsa: 0,
+        imports: [%ExGpgme.Results.Import{error: nil,
+          fingerprint: "95E93F470BCB2E96C648572DFBFA85913EE05E95",
+          status: []}],
+        new_revocations: 0, new_signatures: 0, new_subkeys: 0,
+        new_user_ids: 0, not_imported: 0, secret_considered: 0,
+        secret_imported: 0, secret_unchanged: 0, unchanged: 1,
+        without_user_id: 0}}
+
+      iex> :open_pgp
+      ...> |> ExGpgme.Context.from_protocol!
+      ...> |> ExGpgme.Context.import(File.read!("priv/test/keys/sender_public.asc"),
+      ...>   [:import_clean, :self_sigs_only, {:keep_uid, "mbox = sender@example.com"}])
+      {:ok, %ExGpgme.Results.ImportResult{...}}
+
+      iex> :cms
+      ...> |> ExGpgme.Context.from_protocol!
+      ...> |> ExGpgme.Context.import(File.read!("alice.p12"), passphrase: "secret passphrase")
+      {:ok, %ExGpgme.Results.ImportResult{secret_imported: 1, ...}}
+
+  """
+  @spec import(context :: context, data :: binary, options :: ImportOptions.options)
+    :: {:ok, ImportResult.t} | {:error, String.t}
+  def import(context, data, options \\ []),
+    do: import_with_options(context, data, options)
+
+  @spec import_with_options(context :: context, data :: binary, options :: ImportOptions.options)
+    :: {:ok, ImportResult.t} | {:error, String.t}
+  defp import_with_options(_context, _data, _options), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  See `import/3`.
+
+  """
+  @spec import!(context :: context, data :: binary, options :: ImportOptions.options) :: ImportResult.t | no_return
+  def import!(context, data, options \\ []) do
+    case __MODULE__.import(context, data, options) do
+      {:ok, result} -> result
+      {:error, error} -> raise error
+    end
+  end
+
+  @doc """
+  Imports the keys `keys` into the key ring of the context.
+
+  This is mainly useful for keys that were found with an external key listing, for example on a keyserver. Those are
+  fetched and imported without exporting them first. Keys from a local key listing are reported as unchanged.
+
+  ### Examples
+
+      iex> context = ExGpgme.Context.from_protocol!(:open_pgp)
+      iex> ExGpgme.Context.import!(context, File.read!("priv/test/keys/sender_public.asc"))
+      iex> key = ExGpgme.Context.find_key!(context, "95E93F470BCB2E96C648572DFBFA85913EE05E95")
+      iex> ExGpgme.Context.import_keys(context, [key])
+      {:ok, %ExGpgme.Results.ImportResult{considered: 1, unchanged: 1, ...}}
+
+  """
+  @spec import_keys(context :: context, keys :: [Key.t]) :: {:ok, ImportResult.t} | {:error, String.t}
+  def import_keys(_context, _keys), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  See `import_keys/2`.
+
+  """
+  @spec import_keys!(context :: context, keys :: [Key.t]) :: ImportResult.t | no_return
+  def import_keys!(context, keys) do
+    case import_keys(context, keys) do
+      {:ok, result} -> result
+      {:error, error} -> raise error
+    end
+  end
+
+  @doc """
+  Fetches the keys with the ids `key_ids` from the keyserver and imports them into the key ring.
+
+  The keys are looked up with an external key listing and imported with `import_keys/2`.
+  See `set_keyserver/2` for the keyserver that is used.
+
+  ### Examples
+
+      iex> context = ExGpgme.Context.ephemeral!(:open_pgp)
+      iex> ExGpgme.Context.set_keyserver(context, "hkp://127.0.0.1:11371")
+      iex> ExGpgme.Context.receive_keys(context, ["95E93F470BCB2E96C648572DFBFA85913EE05E95"])
+      {:ok, %ExGpgme.Results.ImportResult{considered: 1, imported: 1, ...}}
+
+  """
+  @spec receive_keys(context :: context, key_ids :: [String.t]) :: {:ok, ImportResult.t} | {:error, String.t}
+  def receive_keys(_context, _key_ids), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  See `receive_keys/2`.
+
+  """
+  @spec receive_keys!(context :: context, key_ids :: [String.t]) :: ImportResult.t | no_return
+  def receive_keys!(context, key_ids) do
+    case receive_keys(context, key_ids) do
+      {:ok, result} -> result
+      {:error, error} -> raise error
+    end
+  end
+
+  @doc """
+  Uploads the keys `keys` to the keyserver with an external export.
+
+  See `set_keyserver/2` for the keyserver that is used.
+
+  ### Examples
+
+      iex> context = ExGpgme.Context.ephemeral!(:open_pgp)
+      iex> ExGpgme.Context.set_keyserver(context, "hkp://127.0.0.1:11371")
+      iex> ExGpgme.Context.import!(context, File.read!("priv/test/keys/sender_public.asc"))
+      iex> key = ExGpgme.Context.find_key!(context, "95E93F470BCB2E96C648572DFBFA85913EE05E95")
+      iex> ExGpgme.Context.send_keys(context, [key])
+      :ok
+
+  """
+  @spec send_keys(context :: context, keys :: [Key.t]) :: :ok | {:error, String.t}
+  def send_keys(_context, _keys), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  See `send_keys/2`.
+
+  """
+  @spec send_keys!(context :: context, keys :: [Key.t]) :: nil | no_return
+  def send_keys!(context, keys) do
+    case send_keys(context, keys) do
+      :ok -> nil
+      {:error, error} -> raise error
+    end
+  end
+
+  @doc """
+  Exports the keys `keys`. The output is ASCII armored if `armor?/1` is `true`, and binary otherwise.
+
+  See `ExGpgme.ExportMode` for the available modes. With the `:cms` protocol, `[:secret, :pkcs12]` exports the
+  certificates with their secret keys as PKCS#12 bundle, protected by the `{:passphrase, passphrase}` mode.
+
+  ### Examples
+
+      iex> context = ExGpgme.Context.from_protocol!(:open_pgp)
+      iex> ExGpgme.Context.set_armor(context, true)
+      iex> ExGpgme.Context.import!(context, File.read!("priv/test/keys/sender_public.asc"))
+      iex> key = ExGpgme.Context.find_key!(context, "95E93F470BCB2E96C648572DFBFA85913EE05E95")
+      iex> ExGpgme.Context.export_keys(context, [key], [:minimal])
+      {:ok, "-----BEGIN PGP PUBLIC KEY BLOCK-----\\n[data]\\n-----END PGP PUBLIC KEY BLOCK-----\\n"}
+
+      iex> context = ExGpgme.Context.from_protocol!(:cms)
+      iex> key = ExGpgme.Context.find_key!(context, "75EF3F0E1D279B4F7E8F2641277C844E65483AA6")
+      iex> ExGpgme.Context.export_keys(context, [key], [:secret, :pkcs12, {:passphrase, "secret passphrase"}])
+      {:ok, <<48, 130, 10, 28, ...>>}
+
+  """
+  @spec export_keys(context :: context, keys :: [Key.t], modes :: ExportMode.modes)
+    :: {:ok, binary} | {:error, String.t}
+  def export_keys(_context, _keys, _modes), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  See `export_keys/3`.
+
+  """
+  @spec export_keys!(context :: context, keys :: [Key.t], modes :: ExportMode.modes) :: binary | no_return
+  def export_keys!(context, keys, modes) do
+    case export_keys(context, keys, modes) do
+      {:ok, result} -> result
+      {:error, error} -> raise error
+    end
+  end
+
+  @doc """
+  Locates the keys of the email address `email` in its Web Key Directory. Found keys are imported into the key ring.
+
+  The lookup uses the `auto-key-locate` mechanism `wkd` of `gpg` and needs network access through `dirmngr`.
+  `dirmngr` only accepts Web Key Directories with a certificate of a system certificate authority.
+  Web Key Directories can be written with `ExGpgme.Wkd.generate/4`.
+
+  ### Examples
+
+      iex> context = ExGpgme.Context.from_protocol!(:open_pgp)
+      iex> ExGpgme.Context.locate_key(context, "alice@example.com")
+      {:ok, [%ExGpgme.Keys.KeyDetails{...}]}
+
+  """
+  @spec locate_key(context :: context, email :: String.t) :: {:ok, [KeyDetails.t]} | {:error, String.t}
+  def locate_key(_context, _email), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  See `locate_key/2`.
+
+  """
+  @spec locate_key!(context :: context, email :: String.t) :: [KeyDetails.t] | no_return
+  def locate_key!(context, email) do
+    case locate_key(context, email) do
+      {:ok, result} -> result
+      {:error, error} -> raise error
+    end
+  end
+
+  @doc """
+  Reads the keys in `data` without changing the key ring of the context.
+
+  The keys are listed with the `import-show` import option of `gpg` in a dry run, no key ring is written. This needs
+  `gpg` 2.1.14 or later and is not supported by the `:cms` protocol. `data` may be armored or binary.
+
+  ### Examples
+
+      iex> :open_pgp
+      ...> |> ExGpgme.Context.from_protocol!
+      ...> |> ExGpgme.Context.inspect_keys(File.read!("priv/test/keys/sender_public.asc"))
+      {:ok,
+       [%ExGpgme.Keys.KeyDetails{fingerprint: "95E93F470BCB2E96C648572DFBFA85913EE05E95",
+         id: "FBFA85913EE05E95", protocol: :open_pgp, subkeys: [...], user_ids: [...], ...}]}
+
+  """
+  @spec inspect_keys(context :: context, data :: binary) :: {:ok, [KeyDetails.t]} | {:error, String.t}
+  def inspect_keys(_context, _data), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  See `inspect_keys/2`.
+
+  """
+  @spec inspect_keys!(context :: context, data :: binary) :: [KeyDetails.t] | no_return
+  def inspect_keys!(context, data) do
+    case inspect_keys(context, data) do
+      {:ok, result} -> result
+      {:error, error} -> raise error
+    end
+  end
+
+  @doc """
+  Gives the details of a key, like its fingerprint, user IDs and subkeys.
+
+  ### Examples
+
+      iex> context = ExGpgme.Context.from_protocol!(:open_pgp)
+      iex> ExGpgme.Context.import!(context, File.read!("priv/test/keys/sender_public.asc"))
+      iex> key = ExGpgme.Context.find_key!(context, "95E93F470BCB2E96C648572DFBFA85913EE05E95")
+      iex> ExGpgme.Context.key_details(key)
+      {:ok,
+       %ExGpgme.Keys.KeyDetails{fingerprint: "95E93F470BCB2E96C648572DFBFA85913EE05E95",
+        id: "FBFA85913EE05E95", protocol: :open_pgp, subkeys: [...], user_ids: [...], ...}}
+
+  """
+  @spec key_details(key :: Key.t) :: {:ok, KeyDetails.t} | {:error, String.t}
+  def key_details(_key), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  See `key_details/1`.
+
+  """
+  @spec key_details!(key :: Key.t) :: KeyDetails.t | no_return
+  def key_details!(key) do
+    case key_details(key) do
+      {:ok, result} -> result
+      {:error, error} -> raise error
+    end
+  end
+
+  @doc """
+  Find a key by Fingerprint
+
+  ### Examples
+
+      iex> context = ExGpgme.Context.from_protocol!(:open_pgp)
+      iex> ExGpgme.Context.import!(context, File.read!("priv/test/keys/sender_public.asc"))
+      iex> ExGpgme.Context.find_key(context, "95E93F470BCB2E96C648572DFBFA85913EE05E95")
+      {:ok, #Reference<0.411470915.3086352388.254522>}
+
+      iex> context = ExGpgme.Context.from_protocol!(:open_pgp)
+      iex> ExGpgme.Context.import!(context, File.read!("priv/test/keys/sender_public.asc"))
+      iex> ExGpgme.Context.find_key(context, "not-existing-fingerprint")
+      {:error, "End of file"}
+
+  """
+  @spec find_key(context :: context, fingerprint :: String.t) :: {:ok, Key.t} | {:error, String.t}
+  def find_key(_context, _fingerprint), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  See `find_key/2`.
+
+  """
+  @spec find_key!(context :: context, fingerprint :: String.t) :: Key.t | no_return
+  def find_key!(context, fingerprint) do
+    case find_key(context, fingerprint) do
+      {:ok, result} -> result
+      {:error, error} -> raise error
+    end
+  end
+
+  @doc """
+  Encrypts a message for the specified recipients.
+
+  ### Examples
+
+      iex> context = ExGpgme.Context.from_protocol!(:open_pgp)
+      iex> ExGpgme.Context.set_armor(context, true)
+      iex> ExGpgme.Context.import!(context, File.read!("priv/test/keys/sender_public.asc"))
+      iex> recipient = ExGpgme.Context.find_key!(context, "95E93F470BCB2E96C648572DFBFA85913EE05E95")
+      iex> ExGpgme.Context.encrypt(context, [recipient], "Hello World", [:always_trust])
+      {:ok,
+       "-----BEGIN PGP MESSAGE-----\\n[data]\\n-----END PGP MESSAGE-----\\n"}
+
+  """
+  @spec encrypt(context :: context, recipients :: [Key.t], data :: binary, flags:: EncryptFlags.flags)
+    :: {:ok, binary} | {:error, String.t}
+  def encrypt(context, recipients, data, flags \\ []),
+    do: encrypt_with_flags(context, recipients, data, flags)
+
+  @spec encrypt_with_flags(context :: context, recipients :: [Key.t], data :: binary, flags:: EncryptFlags.flags)
+    :: {:ok, binary} | {:error, String.t}
+  defp encrypt_with_flags(_context, _recipients, _data, _flags), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  See `encrypt/4`
+  """
+  @spec encrypt!(context :: context, recipients :: [Key.t], data :: binary, flags:: EncryptFlags.flags)
+    :: binary | no_return
+  def encrypt!(context, recipients, data, flags \\ []) do
+    case encrypt(context, recipients, data, flags) do
+      {:ok, cypthertext} -> cypthertext
+      {:error, error} -> raise error
+    end
+  end
+
+  @doc """
+  Encrypts a message with the passphrase `passphrase`. The message can be decrypted with `decrypt_with_passphrase/3`.
+
+  If `recipients` are given, the message can additionally be decrypted by their secret keys.
+
+  The passphrase is passed to the engine with the `:loopback` pinentry mode and is not cached by the agent.
+
+  ### Examples
+
+      iex> context = ExGpgme.Context.from_protocol!(:open_pgp)
+      iex> ExGpgme.Context.set_armor(context, true)
+      iex> ExGpgme.Context.encrypt_symmetric(context, "secret passphrase", "Hello World")
+      {:ok, "-----BEGIN PGP MESSAGE-----\\n[data]\\n-----END PGP MESSAGE-----\\n"}
+
+  """
+  @spec encrypt_symmetric(context :: context, passphrase :: String.t, data :: binary, recipients :: [Key.t],
+    flags :: EncryptFlags.flags)
+    :: {:ok, binary} | {:error, String.t}
+  def encrypt_symmetric(context, passphrase, data, recipients \\ [], flags \\ []),
+    do: encrypt_symmetric_with_flags(context, passphrase, recipients, data, flags)
+
+  @spec encrypt_symmetric_with_flags(context :: context, passphrase :: String.t, recipients :: [Key.t],
+    data :: binary, flags :: EncryptFlags.flags)
+    :: {:ok, binary} | {:error, String.t}
+  defp encrypt_symmetric_with_flags(_context, _passphrase, _recipients, _data, _flags),
+    do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  See `encrypt_symmetric/5`
+  """
+  @spec encrypt_symmetric!(context :: context, passphrase :: String.t, data :: binary, recipients :: [Key.t],
+    flags :: EncryptFlags.flags)
+    :: binary | no_return
+  def encrypt_symmetric!(context, passphrase, data, recipients \\ [], flags \\ []) do
+    case encrypt_symmetric(context, passphrase, data, recipients, flags) do
+      {:ok, cypthertext} -> cypthertext
+      {:error, error} -> raise error
+    end
+  end
+
+  @doc """
+  Signs and encrypts a message for the specified recipients.
+
+  ### Examples
+
+      iex> context = ExGpgme.Context.from_protocol!(:open_pgp)
+      iex> ExGpgme.Context.set_armor(context, true)
+      iex> ExGpgme.Context.import!(context, File.read!("priv/test/keys/sender_public.asc"))
+      iex> recipient = ExGpgme.Context.find_key!(context, "95E93F470BCB2E96C648572DFBFA85913EE05E95")
+      iex> ExGpgme.Context.sign_and_encrypt(context, [recipient], "Hello World", [:always_trust])
+      {:ok, "-----BEGIN PGP MESSAGE-----\\n[data]\\n-----END PGP MESSAGE-----\\n"}
+
+  """
+  @spec sign_and_encrypt(context :: context, recipients :: [Key.t], data :: binary, flags:: EncryptFlags.flags)
+    :: {:ok, binary} | {:error, String.t}
+  def sign_and_encrypt(context, recipients, data, flags \\ []),
+    do: sign_and_encrypt_with_flags(context, recipients, data, flags)
+
+  @spec sign_and_encrypt_with_flags(context :: context, recipients :: [Key.t],
+    data :: binary, flags:: EncryptFlags.flags)
+    :: {:ok, binary} | {:error, String.t}
+  defp sign_and_encrypt_with_flags(_context, _recipients, _data, _flags), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  See `sign_and_encrypt/4`
+  """
+  @spec sign_and_encrypt!(context :: context, recipients :: [Key.t], data :: binary, flags:: EncryptFlags.flags)
+    :: binary | no_return
+  def sign_and_encrypt!(context, recipients, data, flags \\ []) do
+    case sign_and_encrypt(context, recipients, data, flags) do
+      {:ok, cypthertext} -> cypthertext
+      {:error, error} -> raise error
+    end
+  end
+
+  @doc """
+  The function deletes the key `key` from the key ring of the crypto engine used by `context`.
+
+  ### Examples
+
+      iex> context = ExGpgme.Context.from_protocol!(:open_pgp)
+      iex> ExGpgme.Context.import!(context, File.read!("priv/test/keys/sender_public.asc"))
+      iex> key = ExGpgme.Context.find_key!(context, "95E93F470BCB2E96C648572DFBFA85913EE05E95")
+      iex> ExGpgme.Context.delete_key(context, key)
+      :ok
+
+  """
+  @spec delete_key(context :: context, key :: Key.t) :: :ok | {:error, String.t}
+  def delete_key(_context, _key), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  See `delete_key/2`
+
+  """
+  @spec delete_key!(context :: context, key :: Key.t) :: nil | no_return
+  def delete_key!(context, key) do
+    case delete_key(context, key) do
+      :ok -> nil
+      {:error, error} -> raise error
+    end
+  end
+
+  @doc """
+  The function deletes the secret key `key` from the key ring of the crypto engine used by `context`.
+
+  ### Examples
+
+      iex> context = ExGpgme.Context.from_protocol!(:open_pgp)
+      iex> ExGpgme.Context.import!(context, File.read!("priv/test/keys/sender_public.asc"))
+      iex> key = ExGpgme.Context.find_key!(context, "95E93F470BCB2E96C648572DFBFA85913EE05E95")
+      iex> ExGpgme.Context.delete_secret_key(context, key)
       :ok
 
   """
@@ -558,14 +1469,14 @@ defmodule ExGpgme.Context do
       {:ok, "Hello World"}
 
   """
-  @spec decrypt(context :: context, cypertext :: String.t) :: {:ok, String.t} | {:error, String.t}
+  @spec decrypt(context :: context, cypertext :: binary) :: {:ok, binary} | {:error, String.t}
   def decrypt(_context, _cyphertext), do: :erlang.nif_error(:nif_not_loaded)
 
   @doc """
   See `decrypt/2`
 
   """
-  @spec decrypt!(context :: context, cypertext :: String.t) :: String.t | no_return
+  @spec decrypt!(context :: context, cypertext :: binary) :: binary | no_return
   def decrypt!(context, cyphertext) do
     case decrypt(context, cyphertext) do
       {:ok, plaintext} -> plaintext
@@ -573,11 +1484,137 @@ defmodule ExGpgme.Context do
     end
   end
 
+  @doc """
+  Decrypts the ciphertext like `decrypt/2` and returns the audit log of the decryption with the plain text.
+
+  The log is taken right after the decryption on the same engine context. It is only available with the `:cms`
+  protocol and with engines that support it for `:open_pgp`. See `verify_opaque_with_audit_log/4`. When the decryption
+  fails the error comes with the log as `{:error, {message, log}}`.
+
+  ### Examples
+
+      iex> context = ExGpgme.Context.from_protocol!(:cms)
+      iex> ExGpgme.Context.decrypt_with_audit_log(context, File.read!("encrypted.p7m"), :text)
+      {:ok, {"Hello World", "* Data decryption succeeded: Yes\\n[data]"}}
+
+  """
+  @spec decrypt_with_audit_log(context :: context, cypertext :: binary, format :: ExGpgme.audit_log_format)
+    :: {:ok, {binary, String.t}} | {:error, {String.t, String.t}} | {:error, String.t}
+  def decrypt_with_audit_log(_context, _cyphertext, _format), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  See `decrypt_with_audit_log/3`
+
+  """
+  @spec decrypt_with_audit_log!(context :: context, cypertext :: binary, format :: ExGpgme.audit_log_format)
+    :: {binary, String.t} | no_return
+  def decrypt_with_audit_log!(context, cyphertext, format) do
+    case decrypt_with_audit_log(context, cyphertext, format) do
+      {:ok, result} -> result
+      {:error, {error, _log}} -> raise error
+      {:error, error} -> raise error
+    end
+  end
+
+  @doc """
+  The function decrypts the ciphertext in the argument `ciphertext` with the passphrase `passphrase` and returns the
+  plain text. See `encrypt_symmetric/5`.
+
+  ### Examples
+
+      iex> context = ExGpgme.Context.from_protocol!(:open_pgp)
+      iex> cyphertext = ExGpgme.Context.encrypt_symmetric!(context, "secret passphrase", "Hello World")
+      iex> ExGpgme.Context.decrypt_with_passphrase(context, "secret passphrase", cyphertext)
+      {:ok, "Hello World"}
+
+  """
+  @spec decrypt_with_passphrase(context :: context, passphrase :: String.t, cypertext :: binary)
+    :: {:ok, binary} | {:error, String.t}
+  def decrypt_with_passphrase(_context, _passphrase, _cyphertext), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  See `decrypt_with_passphrase/3`
+
+  """
+  @spec decrypt_with_passphrase!(context :: context, passphrase :: String.t, cypertext :: binary)
+    :: binary | no_return
+  def decrypt_with_passphrase!(context, passphrase, cyphertext) do
+    case decrypt_with_passphrase(context, passphrase, cyphertext) do
+      {:ok, plaintext} -> plaintext
+      {:error, error} -> raise error
+    end
+  end
+
+  @doc """
+  The function decrypts the ciphertext in the argument `ciphertext` and returns the plain text together with the
+  decryption result. The session key of the message is exported into the `session_key` of the result.
+
+  The session key can be used with `decrypt_with_session_key/3` to decrypt this single message without the secret key.
+
+  ### Examples
+
+      iex> context = ExGpgme.Context.from_protocol!(:open_pgp)
+      iex> ExGpgme.Context.import!(context, File.read!("priv/test/keys/receiver_secret.asc"))
+      iex> ExGpgme.Context.decrypt_export_session_key(context, File.read!("priv/test/test_data/encrypted_receiver.asc"))
+      {:ok, {"Hello World!", %ExGpgme.Results.DecryptionResult{filename: nil, is_wrong_key_usage: false,
+        session_key: "9:[key]", unsupported_algorithm: nil}}}
+
+  """
+  @spec decrypt_export_session_key(context :: context, cypertext :: binary)
+    :: {:ok, {binary, DecryptionResult.t}} | {:error, String.t}
+  def decrypt_export_session_key(_context, _cyphertext), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  See `decrypt_export_session_key/2`
+
+  """
+  @spec decrypt_export_session_key!(context :: context, cypertext :: binary)
+    :: {binary, DecryptionResult.t} | no_return
+  def decrypt_export_session_key!(context, cyphertext) do
+    case decrypt_export_session_key(context, cyphertext) do
+      {:ok, result} -> result
+      {:error, error} -> raise error
+    end
+  end
+
+  @doc """
+  The function decrypts the ciphertext in the argument `ciphertext` with the session key `session_key` and returns the
+  plain text. No secret key is needed. See `decrypt_export_session_key/2`.
+
+  Please be aware that using this feature with GnuPG < `2.1.16` will leak the session key on many platforms via `ps(1)`.
+
+  ### Examples
+
+      iex> context = ExGpgme.Context.from_protocol!(:open_pgp)
+      iex> ExGpgme.Context.decrypt_with_session_key(context, "9:[key]",
+      ...>   File.read!("priv/test/test_data/encrypted_receiver.asc"))
+      {:ok, "Hello World!"}
+
+  """
+  @spec decrypt_with_session_key(context :: context, session_key :: String.t, cypertext :: binary)
+    :: {:ok, binary} | {:error, String.t}
+  def decrypt_with_session_key(_context, _session_key, _cyphertext), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  See `decrypt_with_session_key/3`
+
+  """
+  @spec decrypt_with_session_key!(context :: context, session_key :: String.t, cypertext :: binary)
+    :: binary | no_return
+  def decrypt_with_session_key!(context, session_key, cyphertext) do
+    case decrypt_with_session_key(context, session_key, cyphertext) do
+      {:ok, plaintext} -> plaintext
+      {:error, error} -> raise error
+    end
+  end
+
   @doc """
   The function creates a signature for the text in the `data`. The type of the signature created is determined by the
   ASCII armor (or, if that is not set, by the encoding specified for sig), the text mode attributes set for the context
   ctx and the requested signature mode `mode`.
 
+  With the `:cms` protocol, the number of certificates included in the signature is set with `set_include_certs/2`.
+
   ### Examples
 
       iex> context = ExGpgme.Context.from_protocol!(:open_pgp)
@@ -587,19 +1624,19 @@ defmodule ExGpgme.Context do
       {:ok, "-----BEGIN PGP MESSAGE-----\\n[data]\\n-----END PGP MESSAGE-----\\n"}
 
   """
-  @spec sign(context :: context, mode :: ExGpgme.sign_mode, data :: String.t)
-    :: {:ok, String.t} | {:error, String.t}
+  @spec sign(context :: context, mode :: ExGpgme.sign_mode, data :: binary)
+    :: {:ok, binary} | {:error, String.t}
   def sign(context, mode \\ :normal, data), do: sign_with_mode(context, mode, data)
 
-  @spec sign_with_mode(context :: context, mode :: ExGpgme.sign_mode, data :: String.t)
-    :: {:ok, String.t} | {:error, String.t}
+  @spec sign_with_mode(context :: context, mode :: ExGpgme.sign_mode, data :: binary)
+    :: {:ok, binary} | {:error, String.t}
   defp sign_with_mode(_context, _mode, _data), do: :erlang.nif_error(:nif_not_loaded)
 
   @doc """
   See `sign/3`
 
   """
-  @spec sign!(context :: context, mode :: ExGpgme.sign_mode, data :: String.t) :: String.t | no_return
+  @spec sign!(context :: context, mode :: ExGpgme.sign_mode, data :: binary) :: binary | no_return
   def sign!(context, mode \\ :normal, data) do
     case sign(context, mode, data) do
       {:ok, signature} -> signature
@@ -622,21 +1659,22 @@ defmodule ExGpgme.Context do
          signatures: [%ExGpgme.Results.Signature{creation_time: 1510648065,
            expiration_time: nil,
            fingerprint: "406B5EE427BA5396C39D0F1DD257FFE3438B29DB",
-           hash_algorithm: :sha512, is_wrong_key_usage: false, key: nil,
+           chain_id: nil, hash_algorithm: :sha512, is_wrong_key_usage: false, issuer_name: nil,
+           issuer_serial: nil, key: nil,
            key_algorithm: :rsa, never_expires: true,
            nonvalidity_reason: nil, notations: [], pka_address: nil,
            pka_trust: :unknown, policy_url: nil, status: :valid,
            validity: :full, verified_by_chain: false}]}}
 
   """
-  @spec verify_opaque(context :: context, signature :: String.t, data :: String.t)
+  @spec verify_opaque(context :: context, signature :: binary, data :: binary)
     :: {:ok, VerificationResult.t} | {:error, String.t}
   def verify_opaque(_context, _signature, _data), do: :erlang.nif_error(:nif_not_loaded)
 
   @doc """
   See `verify_opaque/3`
   """
-  @spec verify_opaque!(context :: context, signature :: String.t, data :: String.t)
+  @spec verify_opaque!(context :: context, signature :: binary, data :: binary)
     :: VerificationResult.t | no_return
   def verify_opaque!(context, signature, data) do
     case verify_opaque(context, signature, data) do
@@ -644,4 +1682,153 @@ defmodule ExGpgme.Context do
       {:error, error} -> raise error
     end
   end
+
+  @doc """
+  Verifies the signature like `verify_opaque/3` and returns the audit log of the verification with the result. The
+  log describes how the signature was verified, including the checks of the certificate chain.
+
+  The log is taken right after the verification on the same engine context, so pools and concurrent callers can not
+  mix up logs. It is only available with the `:cms` protocol and with engines that support it for `:open_pgp`. When
+  the verification fails the error comes with the log as `{:error, {message, log}}`. The log of other operations can
+  be read with `audit_log/2`.
+
+  ### Examples
+
+      iex> context = ExGpgme.Context.from_protocol!(:cms)
+      iex> ExGpgme.Context.verify_opaque_with_audit_log(context, File.read!("signed.p7m"), "Hello World", :html)
+      {:ok, {%ExGpgme.Results.VerificationResult{...}, "<div class=\"GnuPGAuditLog\">\\n[data]</div>\\n"}}
+
+  """
+  @spec verify_opaque_with_audit_log(context :: context, signature :: binary, data :: binary,
+    format :: ExGpgme.audit_log_format)
+    :: {:ok, {VerificationResult.t, String.t}} | {:error, {String.t, String.t}} | {:error, String.t}
+  def verify_opaque_with_audit_log(_context, _signature, _data, _format), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  See `verify_opaque_with_audit_log/4`
+  """
+  @spec verify_opaque_with_audit_log!(context :: context, signature :: binary, data :: binary,
+    format :: ExGpgme.audit_log_format)
+    :: {VerificationResult.t, String.t} | no_return
+  def verify_opaque_with_audit_log!(context, signature, data, format) do
+    case verify_opaque_with_audit_log(context, signature, data, format) do
+      {:ok, result} -> result
+      {:error, {error, _log}} -> raise error
+      {:error, error} -> raise error
+    end
+  end
+
+  @doc """
+  Returns the audit log of the last operation the calling process ran on `context`, for example a signature or a
+  failed decryption.
+
+  The log is read from the engine context of the pool that ran the operation. If another process ran an operation on
+  that engine context in the meantime, its log is gone and an error is returned. Use `verify_opaque_with_audit_log/4`
+  or `decrypt_with_audit_log/3` to get the log together with the result.
+
+  ### Examples
+
+      iex> context = ExGpgme.Context.from_protocol!(:cms)
+      iex> ExGpgme.Context.sign(context, :detached, "Hello World")
+      iex> ExGpgme.Context.audit_log(context, :text)
+      {:ok, "* Data signing succeeded: Yes\n[data]"}
+
+  """
+  @spec audit_log(context :: context, format :: ExGpgme.audit_log_format) :: {:ok, String.t} | {:error, String.t}
+  def audit_log(_context, _format), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  See `audit_log/2`
+
+  """
+  @spec audit_log!(context :: context, format :: ExGpgme.audit_log_format) :: String.t | no_return
+  def audit_log!(context, format) do
+    case audit_log(context, format) do
+      {:ok, result} -> result
+      {:error, error} -> raise error
+    end
+  end
+
+  @typedoc """
+  Option for `create_certificate_request/2`
+
+  * `:subject` - Distinguished name of the subject, for example `"CN=Alice,O=Example"`. Required.
+  * `:key_type` - Algorithm of the new key pair. Defaults to `:rsa`.
+  * `:key_length` - Length of the new key in bits. Defaults to `2048`.
+  * `:key_usage` - Allowed usages of the key. Defaults to `[:sign, :encrypt]`.
+  * `:emails` - Email addresses added as subject alternative names.
+  * `:dns_names` - DNS names added as subject alternative names.
+  * `:passphrase` - Passphrase protecting the new secret key. Without it, the passphrase is asked by the pinentry.
+  """
+  @type certificate_request_option :: {:subject, String.t} |
+    {:key_type, atom} |
+    {:key_length, pos_integer} |
+    {:key_usage, [:sign | :encrypt | :cert]} |
+    {:emails, [String.t]} |
+    {:dns_names, [String.t]} |
+    {:passphrase, String.t}
+
+  @doc """
+  Creates a new key pair and a PKCS#10 certificate signing request for it. Only the `:cms` protocol is supported.
+
+  The secret key is stored by the agent of the context. The request is returned PEM encoded if ASCII armor is enabled
+  (see `set_armor/2`), and DER encoded otherwise. Send it to a certificate authority and import the issued certificate
+  with `import/3`.
+
+  ### Examples
+
+      iex> context = ExGpgme.Context.from_protocol!(:cms, armor: true)
+      iex> ExGpgme.Context.create_certificate_request(context, subject: "CN=Alice,O=Example",
+      ...>   emails: ["alice@example.com"], passphrase: "secret passphrase")
+      {:ok, "-----BEGIN CERTIFICATE REQUEST-----\\n[data]\\n-----END CERTIFICATE REQUEST-----\\n"}
+
+  """
+  @spec create_certificate_request(context :: context, options :: [certificate_request_option])
+    :: {:ok, binary} | {:error, String.t}
+  def create_certificate_request(context, options) do
+    with {:ok, parameters} <- certificate_request_parameters(options) do
+      generate_certificate_request(context, parameters, Keyword.get(options, :passphrase))
+    end
+  end
+
+  @doc """
+  See `create_certificate_request/2`.
+
+  """
+  @spec create_certificate_request!(context :: context, options :: [certificate_request_option])
+    :: binary | no_return
+  def create_certificate_request!(context, options) do
+    case create_certificate_request(context, options) do
+      {:ok, result} -> result
+      {:error, error} -> raise error
+    end
+  end
+
+  @spec certificate_request_parameters(options :: [certificate_request_option]) :: {:ok, String.t} | {:error, String.t}
+  defp certificate_request_parameters(options) do
+    case Keyword.fetch(options, :subject) do
+      {:ok, subject} ->
+        parameters = [
+          {"Key-Type", options |> Keyword.get(:key_type, :rsa) |> Atom.to_string |> String.upcase},
+          {"Key-Length", options |> Keyword.get(:key_length, 2048) |> Integer.to_string},
+          {"Key-Usage", options |> Keyword.get(:key_usage, [:sign, :encrypt]) |> Enum.join(", ")},
+          {"Name-DN", subject},
+        ] ++
+          Enum.map(Keyword.get(options, :emails, []), &{"Name-Email", &1}) ++
+          Enum.map(Keyword.get(options, :dns_names, []), &{"Name-DNS", &1})
+
+        if Enum.any?(parameters, fn {_name, value} -> String.contains?(value, ["\n", "\r"]) end) do
+          {:error, "Certificate request parameters must not contain line breaks"}
+        else
+          lines = Enum.map(parameters, fn {name, value} -> "#{name}: #{value}\n" end)
+          {:ok, IO.iodata_to_binary(["<GnupgKeyParms format=\"internal\">\n", lines, "</GnupgKeyParms>\n"])}
+        end
+      :error ->
+        {:error, "Missing option subject"}
+    end
+  end
+
+  @spec generate_certificate_request(context :: context, parameters :: String.t, passphrase :: String.t | nil)
+    :: {:ok, binary} | {:error, String.t}
+  defp generate_certificate_request(_context, _parameters, _passphrase), do: :erlang.nif_error(:nif_not_loaded)
 end
@@ -0,0 +1,100 @@
+defmodule ExGpgme.Engine do
+  @moduledoc """
+  Information about the `gpgme` library and the crypto engines installed on the host.
+  """
+
+  alias ExGpgme.Context
+  alias ExGpgme.Engine.EngineInfo
+
+  @doc """
+  Returns the version of the `gpgme` library.
+
+  ### Examples
+
+      iex> ExGpgme.Engine.version
+      "1.9.0"
+
+  """
+  @spec version :: String.t
+  def version, do: Context.library_version()
+
+  @doc """
+  Lists the engine info of every protocol (`:open_pgp`, `:cms`, `:gpg_conf`, `:assuan`, `:g13`, `:ui_server` and
+  `:spawn`).
+
+  The `version` of an engine that is not installed is an empty string.
+
+  ### Examples
+
+      iex> ExGpgme.Engine.engine_infos
+      {:ok, [
+        %ExGpgme.Engine.EngineInfo{home_dir: "", path: "/usr/local/MacGPG2/bin/gpg", protocol: :open_pgp,
+          required_version: "1.4.0", version: "2.2.0"},
+        ...
+      ]}
+
+  """
+  @spec engine_infos :: {:ok, [EngineInfo.t]} | {:error, String.t}
+  def engine_infos, do: Context.engine_infos()
+
+  @doc """
+  See `engine_infos/0`.
+
+  """
+  @spec engine_infos! :: [EngineInfo.t] | no_return
+  def engine_infos! do
+    case engine_infos() do
+      {:ok, result} -> result
+      {:error, error} -> raise error
+    end
+  end
+
+  @doc """
+  Checks that the engine for `protocol` is installed and its version is at least `required_version`.
+
+  Use it on application start to refuse running on a host with an unsupported engine.
+
+  ### Examples
+
+      iex> ExGpgme.Engine.check_version(:open_pgp, "2.1.0")
+      :ok
+
+      iex> ExGpgme.Engine.check_version(:open_pgp, "9.0.0")
+      {:error, "Engine :open_pgp version 2.2.0 is older than required version 9.0.0"}
+
+  """
+  @spec check_version(protocol :: ExGpgme.protocol, required_version :: String.t) :: :ok | {:error, String.t}
+  def check_version(protocol, required_version) do
+    with {:ok, engine_infos} <- engine_infos(),
+         {:ok, %EngineInfo{version: version}} <- find_engine_info(engine_infos, protocol),
+         {:ok, comparison} <- Context.compare_versions(version, required_version) do
+      if comparison == :lt do
+        {:error, "Engine #{inspect protocol} version #{version} is older than required version #{required_version}"}
+      else
+        :ok
+      end
+    else
+      {:error, :not_installed} -> {:error, "Engine #{inspect protocol} is not installed"}
+      {:error, error} -> {:error, error}
+    end
+  end
+
+  @doc """
+  See `check_version/2`.
+
+  """
+  @spec check_version!(protocol :: ExGpgme.protocol, required_version :: String.t) :: nil | no_return
+  def check_version!(protocol, required_version) do
+    case check_version(protocol, required_version) do
+      :ok -> nil
+      {:error, error} -> raise error
+    end
+  end
+
+  defp find_engine_info(engine_infos, protocol) do
+    case Enum.find(engine_infos, &(&1.protocol == protocol)) do
+      %EngineInfo{version: version} = engine_info when version not in [nil, ""] -> {:ok, engine_info}
+      _ -> {:error, :not_installed}
+    end
+  end
+end
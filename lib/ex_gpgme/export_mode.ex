@@ -0,0 +1,30 @@
+defmodule ExGpgme.ExportMode do
+  @moduledoc """
+  Holds export modes
+  """
+
+  @typedoc """
+  Modes for export functions
+
+  * `:extern` - Send the keys to the keyserver instead of returning them.
+  * `:minimal` - Remove all signatures except the most recent self-signature on each user ID.
+  * `:secret` - Export the secret keys instead of the public keys.
+  * `:raw` - Export X.509 certificates in raw format instead of PEM.
+  * `:pkcs12` - Export X.509 certificates with their secret keys as PKCS#12 bundle. Must be combined with `:secret`.
+  """
+  @type mode :: :extern |
+    :minimal |
+    :secret |
+    :raw |
+    :pkcs12
+
+  @typedoc """
+  Passphrase for exports that ask for one. With `:pkcs12`, it unlocks the secret key and protects the bundle.
+  """
+  @type passphrase :: {:passphrase, String.t}
+
+  @typedoc """
+  List of modes. At most one passphrase may be given.
+  """
+  @type modes :: list(mode | passphrase)
+end
@@ -0,0 +1,173 @@
+defmodule ExGpgme.GpgConf do
+  @moduledoc """
+  Reads and changes the options of the GnuPG components with `gpgconf`.
+
+  All functions work on the home directory of the given context. Changes are written to the configuration files of
+  that home directory and the running daemons are told to reload them.
+
+  The `gpgconf` binary is the one of the `:gpg_conf` engine, see `ExGpgme.Engine.engine_infos/0`.
+  """
+
+  alias ExGpgme.Context
+  alias ExGpgme.GpgConf.{Component, ComponentOption}
+
+  @type value :: String.t | integer | boolean | nil
+
+  @doc """
+  Starts the component `component` (for example `"gpg-agent"`, `"dirmngr"` or `"keyboxd"`) for the home directory of
+  the context if it is not running yet. `"all"` starts all components.
+
+  ### Examples
+
+      iex> context = ExGpgme.Context.from_protocol!(:open_pgp)
+      iex> ExGpgme.GpgConf.launch(context, "gpg-agent")
+      :ok
+
+  """
+  @spec launch(context :: Context.context, component :: String.t) :: :ok | {:error, String.t}
+  def launch(context, component), do: Context.gpgconf_control(context, :launch, component)
+
+  @doc """
+  Makes the running component `component` reload its configuration. `"all"` reloads all components.
+
+  ### Examples
+
+      iex> context = ExGpgme.Context.from_protocol!(:open_pgp)
+      iex> ExGpgme.GpgConf.reload(context, "gpg-agent")
+      :ok
+
+  """
+  @spec reload(context :: Context.context, component :: String.t) :: :ok | {:error, String.t}
+  def reload(context, component), do: Context.gpgconf_control(context, :reload, component)
+
+  @doc """
+  Stops the component `component` of the home directory of the context. `"all"` stops all components.
+
+  See `ExGpgme.Context.set_kill_on_drop/2` to stop them when the context is garbage collected.
+
+  ### Examples
+
+      iex> context = ExGpgme.Context.from_protocol!(:open_pgp)
+      iex> ExGpgme.GpgConf.kill(context, "all")
+      :ok
+
+  """
+  @spec kill(context :: Context.context, component :: String.t) :: :ok | {:error, String.t}
+  def kill(context, component), do: Context.gpgconf_control(context, :kill, component)
+
+  @doc """
+  Lists the components with their options.
+
+  ### Examples
+
+      iex> context = ExGpgme.Context.from_protocol!(:open_pgp)
+      iex> ExGpgme.GpgConf.components(context)
+      {:ok, [
+        %ExGpgme.GpgConf.Component{name: "gpg-agent", description: "Private Keys", path: "/usr/bin/gpg-agent",
+          options: [
+            %ExGpgme.GpgConf.ComponentOption{name: "default-cache-ttl", description: "expire cached PINs after N seconds",
+              flags: [:runtime], level: :basic, type: :uint32, arg_name: "N", default: "600", value: nil},
+            ...
+          ]},
+        ...
+      ]}
+
+  """
+  @spec components(context :: Context.context) :: {:ok, [Component.t]} | {:error, String.t}
+  def components(context), do: Context.gpgconf_components(context)
+
+  @doc """
+  See `components/1`.
+
+  """
+  @spec components!(context :: Context.context) :: [Component.t] | no_return
+  def components!(context) do
+    case components(context) do
+      {:ok, result} -> result
+      {:error, error} -> raise error
+    end
+  end
+
+  @doc """
+  Gives a single component with its options.
+
+  ### Examples
+
+      iex> context = ExGpgme.Context.from_protocol!(:open_pgp)
+      iex> ExGpgme.GpgConf.component(context, "dirmngr")
+      {:ok, %ExGpgme.GpgConf.Component{name: "dirmngr", description: "Network", path: "/usr/bin/dirmngr",
+        options: [...]}}
+
+      iex> context = ExGpgme.Context.from_protocol!(:open_pgp)
+      iex> ExGpgme.GpgConf.component(context, "foo")
+      {:error, "Unknown component foo"}
+
+  """
+  @spec component(context :: Context.context, name :: String.t) :: {:ok, Component.t} | {:error, String.t}
+  def component(context, name) do
+    with {:ok, components} <- components(context) do
+      case Enum.find(components, &(&1.name == name)) do
+        nil -> {:error, "Unknown component #{name}"}
+        component -> {:ok, component}
+      end
+    end
+  end
+
+  @doc """
+  Gives the option `option` of the component `component`.
+
+  ### Examples
+
+      iex> context = ExGpgme.Context.from_protocol!(:open_pgp)
+      iex> ExGpgme.GpgConf.option(context, "gpg-agent", "default-cache-ttl")
+      {:ok, %ExGpgme.GpgConf.ComponentOption{name: "default-cache-ttl", description: "expire cached PINs after N seconds",
+        flags: [:runtime], level: :basic, type: :uint32, arg_name: "N", default: "600", value: nil}}
+
+  """
+  @spec option(context :: Context.context, component :: String.t, option :: String.t)
+    :: {:ok, ComponentOption.t} | {:error, String.t}
+  def option(context, component, option) do
+    with {:ok, %Component{options: options}} <- component(context, component) do
+      case Enum.find(options, &(&1.name == option)) do
+        nil -> {:error, "Unknown option #{option} of component #{component}"}
+        component_option -> {:ok, component_option}
+      end
+    end
+  end
+
+  @doc """
+  Changes the option `option` of the component `component` and writes it to the configuration file.
+
+  * A string is written as a string value.
+  * An integer is written as a number.
+  * `true` enables an option without argument.
+  * `false` and `nil` remove the option, so the default is used again.
+
+  ### Examples
+
+      iex> context = ExGpgme.Context.from_protocol!(:open_pgp)
+      iex> ExGpgme.GpgConf.change_option(context, "gpg-agent", "default-cache-ttl", 300)
+      :ok
+
+      iex> context = ExGpgme.Context.from_protocol!(:open_pgp)
+      iex> ExGpgme.GpgConf.change_option(context, "dirmngr", "keyserver", "hkps://keys.openpgp.org")
+      :ok
+
+  """
+  @spec change_option(context :: Context.context, component :: String.t, option :: String.t, value :: value)
+    :: :ok | {:error, String.t}
+  def change_option(context, component, option, value), do: Context.gpgconf_change_option(context, component, option, value)
+
+  @doc """
+  See `change_option/4`.
+
+  """
+  @spec change_option!(context :: Context.context, component :: String.t, option :: String.t, value :: value)
+    :: nil | no_return
+  def change_option!(context, component, option, value) do
+    case change_option(context, component, option, value) do
+      :ok -> nil
+      {:error, error} -> raise error
+    end
+  end
+end
@@ -0,0 +1,22 @@
+defmodule ExGpgme.GpgConf.Component do
+  @moduledoc """
+  Component of the GnuPG installation like `gpg`, `gpg-agent` or `dirmngr`.
+  """
+
+  alias ExGpgme.GpgConf.ComponentOption
+
+  @type t :: %__MODULE__{
+    name: String.t,
+    description: String.t,
+    path: String.t,
+    options: [ComponentOption.t],
+  }
+
+  @enforce_keys [
+    :name,
+    :description,
+    :path,
+    :options,
+  ]
+  defstruct @enforce_keys
+end
@@ -0,0 +1,54 @@
+defmodule ExGpgme.GpgConf.ComponentOption do
+  @moduledoc """
+  Option of a `ExGpgme.GpgConf.Component`.
+
+  `default` and `value` are `nil` if not set. String values are unescaped and returned without quoting.
+  """
+
+  @type flag :: :optional_arg |
+    :list |
+    :runtime |
+    :no_change
+
+  @type level :: :basic |
+    :advanced |
+    :expert |
+    :invisible |
+    :internal |
+    {:other, non_neg_integer}
+
+  @type type :: :none |
+    :string |
+    :int32 |
+    :uint32 |
+    :pathname |
+    :ldap_server |
+    :key_fpr |
+    :pub_key |
+    :sec_key |
+    :alias_list |
+    {:other, non_neg_integer}
+
+  @type t :: %__MODULE__{
+    name: String.t,
+    description: String.t,
+    flags: [flag],
+    level: level,
+    type: type,
+    arg_name: String.t,
+    default: String.t | nil,
+    value: String.t | nil,
+  }
+
+  @enforce_keys [
+    :name,
+    :description,
+    :flags,
+    :level,
+    :type,
+    :arg_name,
+    :default,
+    :value,
+  ]
+  defstruct @enforce_keys
+end
@@ -0,0 +1,50 @@
+defmodule ExGpgme.ImportOptions do
+  @moduledoc """
+  Holds import options
+  """
+
+  @typedoc """
+  Options for import functions. They map to the `--import-options` of `gpg`.
+
+  * `:import_local_sigs` - Allow importing key signatures marked as local.
+  * `:keep_ownertrust` - Do not clear the ownertrust values during import.
+  * `:repair_pks_subkey_bug` - Repair subkey damage done by the PKS keyserver.
+  * `:import_minimal` - Remove all signatures except the most recent self-signature on each user ID.
+  * `:import_clean` - Remove unusable parts from the key after import.
+  * `:self_sigs_only` - Accept only self-signatures while importing a key.
+  * `:repair_keys` - Repair known problems of keys on import.
+  * `:import_drop_uids` - Do not import any user ID or their binding signatures.
+  * `:restore` - Import in key restore mode.
+  * `:merge_only` - Only update keys that already exist in the key ring.
+  """
+  @type option :: :import_local_sigs |
+    :keep_ownertrust |
+    :repair_pks_subkey_bug |
+    :import_minimal |
+    :import_clean |
+    :self_sigs_only |
+    :repair_keys |
+    :import_drop_uids |
+    :restore |
+    :merge_only
+
+  @typedoc """
+  Filter for import functions. It maps to the `--import-filter` of `gpg`.
+
+  The expression is passed as is, for example `{:keep_uid, "mbox = alice@example.com"}`.
+  """
+  @type filter :: {:keep_uid, String.t} |
+    {:drop_subkey, String.t} |
+    {:drop_sig, String.t}
+
+  @typedoc """
+  Passphrase for imports that ask for one, like PKCS#12 bundles of the `:cms` protocol. The passphrase unlocks the
+  bundle and protects the imported secret key.
+  """
+  @type passphrase :: {:passphrase, String.t}
+
+  @typedoc """
+  List of options. At most one filter and one passphrase may be given.
+  """
+  @type options :: list(option | filter | passphrase)
+end
@@ -0,0 +1,52 @@
+defmodule ExGpgme.Keys.KeyDetails do
+  @moduledoc """
+  Key Details
+
+  `issuer_serial`, `issuer_name` and `chain_id` are only set for X.509 certificates of the `:cms` protocol.
+  """
+
+  alias ExGpgme.Keys.{Subkey, UserId}
+
+  @type t :: %__MODULE__{
+    id: String.t | nil,
+    fingerprint: String.t | nil,
+    protocol: ExGpgme.protocol,
+    owner_trust: ExGpgme.validity,
+    has_secret: boolean,
+    is_revoked: boolean,
+    is_expired: boolean,
+    is_disabled: boolean,
+    is_invalid: boolean,
+    can_encrypt: boolean,
+    can_sign: boolean,
+    can_certify: boolean,
+    can_authenticate: boolean,
+    user_ids: [UserId.t],
+    subkeys: [Subkey.t],
+    issuer_serial: String.t | nil,
+    issuer_name: String.t | nil,
+    chain_id: String.t | nil,
+  }
+
+  @enforce_keys [
+    :id,
+    :fingerprint,
+    :protocol,
+    :owner_trust,
+    :has_secret,
+    :is_revoked,
+    :is_expired,
+    :is_disabled,
+    :is_invalid,
+    :can_encrypt,
+    :can_sign,
+    :can_certify,
+    :can_authenticate,
+    :user_ids,
+    :subkeys,
+    :issuer_serial,
+    :issuer_name,
+    :chain_id,
+  ]
+  defstruct @enforce_keys
+end
@@ -0,0 +1,46 @@
+defmodule ExGpgme.Keys.Subkey do
+  @moduledoc """
+  Subkey of a Key
+  """
+
+  @type t :: %__MODULE__{
+    id: String.t | nil,
+    fingerprint: String.t | nil,
+    keygrip: String.t | nil,
+    key_algorithm: ExGpgme.key_algorithm,
+    length: non_neg_integer,
+    curve: String.t | nil,
+    creation_time: non_neg_integer | nil,
+    expiration_time: non_neg_integer | nil,
+    is_revoked: boolean,
+    is_expired: boolean,
+    is_disabled: boolean,
+    is_invalid: boolean,
+    is_secret: boolean,
+    can_encrypt: boolean,
+    can_sign: boolean,
+    can_certify: boolean,
+    can_authenticate: boolean,
+  }
+
+  @enforce_keys [
+    :id,
+    :fingerprint,
+    :keygrip,
+    :key_algorithm,
+    :length,
+    :curve,
+    :creation_time,
+    :expiration_time,
+    :is_revoked,
+    :is_expired,
+    :is_disabled,
+    :is_invalid,
+    :is_secret,
+    :can_encrypt,
+    :can_sign,
+    :can_certify,
+    :can_authenticate,
+  ]
+  defstruct @enforce_keys
+end
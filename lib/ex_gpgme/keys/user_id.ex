@@ -0,0 +1,26 @@
+defmodule ExGpgme.Keys.UserId do
+  @moduledoc """
+  User ID of a Key
+  """
+
+  @type t :: %__MODULE__{
+    id: String.t | nil,
+    name: String.t | nil,
+    email: String.t | nil,
+    comment: String.t | nil,
+    validity: ExGpgme.validity,
+    is_revoked: boolean,
+    is_invalid: boolean,
+  }
+
+  @enforce_keys [
+    :id,
+    :name,
+    :email,
+    :comment,
+    :validity,
+    :is_revoked,
+    :is_invalid,
+  ]
+  defstruct @enforce_keys
+end
@@ -3,6 +3,11 @@ defmodule ExGpgme.Notation.SignatureNotation do
   Signature Notation
   """
 
+  @typedoc """
+  Flag of a notation added with `ExGpgme.Context.add_signature_notation/4`.
+  """
+  @type flag :: :human_readable | :critical
+
   @type t :: %__MODULE__{
     is_human_readable: boolean,
     is_critical: boolean,
@@ -0,0 +1,24 @@
+defmodule ExGpgme.Results.AssuanResult do
+  @moduledoc """
+  Result of an Assuan transaction.
+
+  * `data` - The data lines sent by the server, joined and unescaped.
+  * `status` - The status lines as `{keyword, args}` in the order they were received.
+  * `inquiries` - The inquiries of the server as `{name, args}` in the order they were received.
+  """
+
+  @type line :: {String.t, String.t | nil}
+
+  @type t :: %__MODULE__{
+    data: binary,
+    status: [line],
+    inquiries: [line],
+  }
+
+  @enforce_keys [
+    :data,
+    :status,
+    :inquiries,
+  ]
+  defstruct @enforce_keys
+end
@@ -0,0 +1,20 @@
+defmodule ExGpgme.Results.DecryptionResult do
+  @moduledoc """
+  Decryption Result
+  """
+
+  @type t :: %__MODULE__{
+    filename: String.t | nil,
+    unsupported_algorithm: String.t | nil,
+    is_wrong_key_usage: boolean,
+    session_key: String.t | nil,
+  }
+
+  @enforce_keys [
+    :filename,
+    :unsupported_algorithm,
+    :is_wrong_key_usage,
+    :session_key,
+  ]
+  defstruct @enforce_keys
+end
@@ -3,12 +3,31 @@ defmodule ExGpgme.Results.Import do
   Import struct for any key in an import.
   """
 
+  @typedoc """
+  Import Status Flag
+
+  * `:new` - The key was new.
+  * `:new_user_id` - The key contained new user IDs.
+  * `:new_signature` - The key contained new signatures.
+  * `:new_subkey` - The key contained new sub keys.
+  * `:secret` - The key contained a secret key.
+  """
+  @type status_flag :: :new |
+    :new_user_id |
+    :new_signature |
+    :new_subkey |
+    :secret
+
   @type t :: %__MODULE__{
-    fingerprint: String.t,
+    fingerprint: String.t | nil,
+    status: [status_flag],
+    error: String.t | nil,
   }
 
   @enforce_keys [
     :fingerprint,
+    :status,
+    :error,
   ]
   defstruct @enforce_keys
 end
@@ -1,6 +1,8 @@
 defmodule ExGpgme.Results.Signature do
   @moduledoc """
   Signature
+
+  `issuer_serial`, `issuer_name` and `chain_id` are only set for X.509 signatures of the `:cms` protocol.
   """
 
   alias ExGpgme.Keys.Key
@@ -26,6 +28,9 @@ defmodule ExGpgme.Results.Signature do
     policy_url: String.t | nil,
     notations: [SignatureNotation.t],
     key: Key.t | nil,
+    issuer_serial: String.t | nil,
+    issuer_name: String.t | nil,
+    chain_id: String.t | nil,
   }
 
   @enforce_keys [
@@ -45,6 +50,9 @@ defmodule ExGpgme.Results.Signature do
     :policy_url,
     :notations,
     :key,
+    :issuer_serial,
+    :issuer_name,
+    :chain_id,
   ]
   defstruct @enforce_keys
 end
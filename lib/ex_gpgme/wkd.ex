@@ -0,0 +1,148 @@
+defmodule ExGpgme.Wkd do
+  @moduledoc """
+  Web Key Directory
+
+  Keys published in a Web Key Directory can be looked up with `ExGpgme.Context.locate_key/2`.
+  """
+
+  alias ExGpgme.Context
+  alias ExGpgme.Keys.{Key, KeyDetails, UserId}
+  alias ExGpgme.Results.{ImportResult, Import}
+
+  @zbase32_alphabet "ybndrfg8ejkmcpqxot1uwisza345h769"
+
+  @doc """
+  Gives the z-base-32 encoded SHA-1 hash of the lowercased `local_part` of an email address. It is used as the file
+  name of the key in the `hu` directory.
+
+  ### Examples
+
+      iex> ExGpgme.Wkd.hash_local_part("Joe.Doe")
+      "iy9q119eutrkn8s1mk4r39qejnbu3n5q"
+
+  """
+  @spec hash_local_part(local_part :: String.t) :: String.t
+  def hash_local_part(local_part) do
+    :sha
+    |> :crypto.hash(String.downcase(local_part))
+    |> zbase32
+  end
+
+  @doc """
+  Writes a Web Key Directory for all user IDs of `keys` that belong to `domain` into `directory`.
+
+  The directory contains a `policy` file and a `hu` directory with the minimal exported keys. Serve it as
+  `https://<domain>/.well-known/openpgpkey/` (direct method) or as
+  `https://openpgpkey.<domain>/.well-known/openpgpkey/<domain>/` (advanced method).
+
+  Every file only holds the user IDs with the mailbox it is published for. The keys are reduced on a private context
+  created with `ExGpgme.Context.ephemeral/2` and exported in binary, `context` is not changed. No network access is
+  needed.
+
+  ### Examples
+
+      iex> context = ExGpgme.Context.from_protocol!(:open_pgp)
+      iex> ExGpgme.Context.import!(context, File.read!("priv/test/keys/sender_public.asc"))
+      iex> key = ExGpgme.Context.find_key!(context, "95E93F470BCB2E96C648572DFBFA85913EE05E95")
+      iex> ExGpgme.Wkd.generate(context, [key], "example.com", "/tmp/wkd")
+      {:ok, ["/tmp/wkd/hu/[hash]"]}
+
+  """
+  @spec generate(context :: Context.context, keys :: [Key.t], domain :: String.t, directory :: Path.t)
+    :: {:ok, [Path.t]} | {:error, String.t}
+  def generate(context, keys, domain, directory) do
+    hu_directory = Path.join(directory, "hu")
+
+    with {:ok, entries} <- collect_entries(context, keys, String.downcase(domain)),
+         :ok <- File.mkdir_p(hu_directory),
+         :ok <- File.write(Path.join(directory, "policy"), ""),
+         {:ok, paths} <- write_entries(entries, hu_directory) do
+      {:ok, paths}
+    else
+      {:error, error} when is_atom(error) -> {:error, to_string(:file.format_error(error))}
+      {:error, error} -> {:error, error}
+    end
+  end
+
+  @doc """
+  See `generate/4`.
+
+  """
+  @spec generate!(context :: Context.context, keys :: [Key.t], domain :: String.t, directory :: Path.t)
+    :: [Path.t] | no_return
+  def generate!(context, keys, domain, directory) do
+    case generate(context, keys, domain, directory) do
+      {:ok, result} -> result
+      {:error, error} -> raise error
+    end
+  end
+
+  defp collect_entries(context, keys, domain) do
+    Enum.reduce_while(keys, {:ok, %{}}, fn key, {:ok, entries} ->
+      with {:ok, %KeyDetails{user_ids: user_ids}} <- Context.key_details(key),
+           {:ok, exported} <- Context.export_keys(context, [key], [:minimal]),
+           {:ok, entries} <- add_mailboxes(entries, exported, mailboxes(user_ids, domain)) do
+        {:cont, {:ok, entries}}
+      else
+        {:error, error} -> {:halt, {:error, error}}
+      end
+    end)
+  end
+
+  defp mailboxes(user_ids, domain) do
+    user_ids
+    |> Enum.flat_map(&mailbox(&1, domain))
+    |> Enum.uniq_by(fn {local_part, _email} -> local_part end)
+  end
+
+  defp mailbox(%UserId{email: email, is_revoked: false, is_invalid: false}, domain) when is_binary(email) do
+    case String.split(email, "@") do
+      [local_part, email_domain] ->
+        if String.downcase(email_domain) == domain, do: [{String.downcase(local_part), email}], else: []
+      _ ->
+        []
+    end
+  end
+  defp mailbox(%UserId{}, _domain), do: []
+
+  defp add_mailboxes(entries, exported, mailboxes) do
+    Enum.reduce_while(mailboxes, {:ok, entries}, fn {local_part, email}, {:ok, acc} ->
+      case export_mailbox(exported, email) do
+        {:ok, data} -> {:cont, {:ok, Map.update(acc, hash_local_part(local_part), [data], &(&1 ++ [data]))}}
+        {:error, error} -> {:halt, {:error, error}}
+      end
+    end)
+  end
+
+  defp export_mailbox(exported, email) do
+    with {:ok, context} <- Context.ephemeral(:open_pgp),
+         {:ok, %ImportResult{imports: [%Import{fingerprint: fingerprint} | _]}} <-
+           Context.import(context, exported, [{:keep_uid, "mbox = #{email}"}]),
+         {:ok, key} <- Context.find_key(context, fingerprint) do
+      Context.export_keys(context, [key], [:minimal])
+    else
+      {:error, error} -> {:error, error}
+      {:ok, %ImportResult{}} -> {:error, "Could not reduce key to #{email}"}
+    end
+  end
+
+  defp write_entries(entries, hu_directory) do
+    Enum.reduce_while(entries, {:ok, []}, fn {hash, exports}, {:ok, paths} ->
+      path = Path.join(hu_directory, hash)
+
+      case File.write(path, exports) do
+        :ok -> {:cont, {:ok, paths ++ [path]}}
+        {:error, error} -> {:halt, {:error, error}}
+      end
+    end)
+  end
+
+  defp zbase32(<<index::5, rest::bitstring>>),
+    do: String.at(@zbase32_alphabet, index) <> zbase32(rest)
+  defp zbase32(<<>>), do: ""
+  defp zbase32(rest) do
+    bits = bit_size(rest)
+    <<index::size(bits)>> = rest
+    zbase32(<<index::size(bits), 0::size(5 - bits)>>)
+  end
+end
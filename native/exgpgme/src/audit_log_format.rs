@@ -0,0 +1,11 @@
+use gpgme;
+use gpgme::AuditLogFlags;
+use rustler::{NifTerm, NifError};
+
+pub fn arg_to_audit_log_flags(arg: NifTerm) -> Result<AuditLogFlags, NifError> {
+    match arg.atom_to_string()?.as_ref() {
+        "text" => Ok(AuditLogFlags::empty()),
+        "html" => Ok(gpgme::AUDIT_LOG_HTML),
+        _ => Err(NifError::BadArg)
+    }
+}
@@ -0,0 +1,54 @@
+use rustler::{NifEnv, NifTerm, NifEncoder};
+use rustler::types::atom::NifAtom;
+use rustler::types::map;
+use gpgme::Context;
+use engine;
+
+mod atoms {
+    rustler_atoms! {
+        atom pinentry_loopback;
+        atom sender;
+        atom session_key_export;
+        atom override_session_key;
+        atom quick_key_creation;
+        atom tofu;
+        atom locate_key;
+        atom import_filter;
+        atom no_symkey_cache;
+    }
+}
+
+const CAPABILITIES: &'static [(fn() -> NifAtom, &'static str, Option<&'static str>)] = &[
+    (atoms::pinentry_loopback, "1.4.0", Some("2.1.0")),
+    (atoms::sender, "1.8.0", Some("2.1.15")),
+    (atoms::session_key_export, "1.8.0", Some("2.1.0")),
+    (atoms::override_session_key, "1.8.0", Some("2.1.0")),
+    (atoms::quick_key_creation, "1.7.0", Some("2.1.13")),
+    (atoms::tofu, "1.7.0", Some("2.1.10")),
+    (atoms::locate_key, "1.13.0", Some("2.1.12")),
+    (atoms::import_filter, "1.14.0", Some("2.1.14")),
+    (atoms::no_symkey_cache, "1.12.0", Some("2.2.7")),
+];
+
+pub fn capabilities_to_term<'a>(context: &Context, env: NifEnv<'a>) -> NifTerm<'a> {
+    let library_version = engine::library_version_string();
+    let engine_info = context.engine_info();
+    let engine_version = match engine_info.version() {
+        Ok(version) if !version.is_empty() => Some(version),
+        _ => None
+    };
+
+    let mut capabilities = map::map_new(env);
+    for &(name, required_library_version, required_engine_version) in CAPABILITIES.iter() {
+        let supported = engine::version_at_least(library_version, required_library_version) && match required_engine_version {
+            Some(required_engine_version) => engine_version
+                .map(| version | engine::version_at_least(version, required_engine_version))
+                .unwrap_or(false),
+            None => true
+        };
+
+        capabilities = capabilities.map_put(name().encode(env), supported.encode(env)).ok().unwrap();
+    }
+
+    capabilities
+}
@@ -1,3 +1,6 @@
+use rustler::{NifEnv, NifTerm};
+use rustler::types::binary::OwnedNifBinary;
+
 pub mod atoms {
     rustler_atoms! {
         atom ok;
@@ -14,35 +17,52 @@ macro_rules! try_gpgme {
     })
 }
 
+macro_rules! try_or_error {
+    ($expr:expr, $env:expr) => (match $expr {
+        Ok(val) => val,
+        Err(err) => {
+            return Ok((::context::helpers::atoms::error(), err).encode($env))
+        }
+    })
+}
+
 macro_rules! context_getter {
     ($name:ident, $context:ident, $env:ident, $body:expr) => (
-        pub fn $name<'a>($env: $crate::rustler::NifEnv<'a>, args: &[$crate::rustler::NifTerm<'a>])
+        pub fn $name<'a>(caller_env: $crate::rustler::NifEnv<'a>, args: &[$crate::rustler::NifTerm<'a>])
         -> $crate::rustler::NifResult<$crate::rustler::NifTerm<'a>> {
-            unpack_immutable_context!($context, args[0]);
-            Ok($body)
+            unpack_context!(resource, args[0]);
+
+            resource.checkout().run_in_env(caller_env, move | $env, $context | Ok($body))
         }
     );
 }
 
 macro_rules! context_setter {
     ($name:ident, $context:ident, $env:ident, $arg: ident, $type:ident, $body:expr) => (
-        pub fn $name<'a>($env: $crate::rustler::NifEnv<'a>, args: &[$crate::rustler::NifTerm<'a>])
+        pub fn $name<'a>(caller_env: $crate::rustler::NifEnv<'a>, args: &[$crate::rustler::NifTerm<'a>])
         -> $crate::rustler::NifResult<$crate::rustler::NifTerm<'a>> {
-            unpack_mutable_context!($context, args[0]);
+            unpack_context!(resource, args[0]);
             let $arg: $type = args[1].decode()?;
 
-            $body;
+            resource.run_on_all(caller_env, move | $env, $context | {
+                $body;
 
-            Ok(::context::helpers::atoms::ok().encode($env))
+                Ok(::context::helpers::atoms::ok().encode($env))
+            })
         }
     )
 }
 
-macro_rules! decode_context_result {
-    ($name:ident, $env:ident) => (
-        match String::from_utf8($name) {
-            Ok(string) => Ok((::context::helpers::atoms::ok(), string).encode($env)),
-            Err(_) => Ok((::context::helpers::atoms::error(), String::from("Could not decode cyphertext to utf8")).encode($env))
-        }
-    )
+macro_rules! binary_context_result {
+    ($name:ident, $env:ident) => ({
+        let mut binary = $crate::rustler::types::binary::OwnedNifBinary::new($name.len()).unwrap();
+        binary.as_mut_slice().copy_from_slice(&$name);
+        Ok((::context::helpers::atoms::ok(), binary.release($env)).encode($env))
+    })
+}
+
+pub fn binary_to_term<'a>(env: NifEnv<'a>, data: &[u8]) -> NifTerm<'a> {
+    let mut binary = OwnedNifBinary::new(data.len()).unwrap();
+    binary.as_mut_slice().copy_from_slice(data);
+    binary.release(env)
 }
@@ -1,20 +1,44 @@
-use rustler::{NifEnv, NifTerm, NifResult, NifEncoder};
+use rustler::{NifEnv, NifTerm, NifResult, NifEncoder, NifError};
+use rustler::TermType;
 use rustler::resource::ResourceArc;
 use rustler::types::list::NifListIterator;
+use rustler::types::binary::NifBinary;
+use rustler::types::atom::nil;
 use gpgme::{Context, EncryptFlags};
+use gpgme;
 use gpgme::keys::Key;
 use std::ops::Deref;
+use std::io::Write;
+use std::sync::Arc;
+use std::sync::atomic::Ordering;
 use results::verification_result::transform_verification_result;
 use keys;
 use protocol;
 use encrypt_flags;
+use import_options;
+use export_mode;
 use engine;
+use capabilities;
 use pinentry_mode;
 use sign_mode;
+use audit_log_format;
+use key_list_mode;
+use passphrase;
+use context_flag::with_flag;
+use home_dir::TemporaryHomeDir;
+use gpgconf;
+use keys::key_details::transform_key_details;
 use results::import_result::transform_import_result;
+use results::decryption_result::transform_decryption_result;
+use results::assuan_result::transform_assuan_result;
+use notation::signature_notation::transform_signature_notation;
+use notation::signature_notation_flags;
 
 #[macro_use] pub mod helpers;
 #[macro_use] pub mod resource;
+pub mod options;
+pub mod settings;
+pub mod worker;
 
 mod atoms {
     rustler_atoms! {
@@ -27,9 +51,85 @@ mod atoms {
 pub fn from_protocol<'a>(env: NifEnv<'a>, args: &[NifTerm<'a>]) -> NifResult<NifTerm<'a>> {
     let protocol = protocol::arg_to_protocol(args[0])?;
 
+    let context_options = args[1].decode::<NifListIterator>()?;
+
     let context = try_gpgme!(Context::from_protocol(protocol), env);
 
-    Ok((atoms::ok(), resource::wrap_context(context)).encode(env))
+    create_resource(env, context, context_options, None)
+}
+
+pub fn ephemeral<'a>(env: NifEnv<'a>, args: &[NifTerm<'a>]) -> NifResult<NifTerm<'a>> {
+    let protocol = protocol::arg_to_protocol(args[0])?;
+
+    let context_options = args[1].decode::<NifListIterator>()?;
+
+    let home_dir = match TemporaryHomeDir::create() {
+        Ok(home_dir) => home_dir,
+        Err(error) => return Ok((atoms::error(), error.to_string()).encode(env))
+    };
+    try_or_error!(home_dir.write_file("gpg-agent.conf", "allow-loopback-pinentry\n").map_err(| error | error.to_string()), env);
+    let home_dir_path = match home_dir.path().to_str() {
+        Some(path) => String::from(path),
+        None => return Ok((atoms::error(), String::from("Could not decode home dir to utf8")).encode(env))
+    };
+
+    let mut context = try_gpgme!(Context::from_protocol(protocol), env);
+    try_gpgme!(context.set_engine_home_dir(home_dir_path), env);
+    try_gpgme!(context.set_pinentry_mode(gpgme::PinentryMode::Loopback), env);
+
+    create_resource(env, context, context_options, Some(home_dir))
+}
+
+fn create_resource<'a>(env: NifEnv<'a>, mut context: Context, context_options: NifListIterator<'a>, temporary_home_dir: Option<TemporaryHomeDir>) -> NifResult<NifTerm<'a>> {
+    let context_options = try_or_error!(options::apply_options(&mut context, context_options), env);
+
+    let mut contexts: Vec<Context> = Vec::with_capacity(context_options.pool_size);
+    for _ in 1..context_options.pool_size {
+        contexts.push(try_gpgme!(settings::clone_context(&context), env));
+    }
+    contexts.insert(0, context);
+
+    let resource = try_or_error!(resource::wrap_contexts(contexts), env);
+    *resource.keyserver.write().unwrap() = context_options.keyserver;
+    resource.kill_on_drop.store(context_options.kill_on_drop, Ordering::SeqCst);
+    *resource.temporary_home_dir.lock().unwrap() = temporary_home_dir.map(Arc::new);
+
+    Ok((atoms::ok(), resource).encode(env))
+}
+
+pub fn settings<'a>(env: NifEnv<'a>, args: &[NifTerm<'a>]) -> NifResult<NifTerm<'a>> {
+    unpack_context!(resource, args[0]);
+
+    let keyserver = resource.keyserver.read().unwrap().clone();
+    let kill_on_drop = resource.kill_on_drop.load(Ordering::SeqCst);
+    let pool_size = resource.usable_workers().len();
+
+    resource.checkout().run_in_env(env, move | env, context | {
+        match settings::settings_to_term(env, context, pool_size, &keyserver, kill_on_drop) {
+            Ok(result) => Ok((atoms::ok(), result).encode(env)),
+            Err(_) => Ok((atoms::error(), String::from("Could not decode settings to utf8")).encode(env))
+        }
+    })
+}
+
+pub fn clone<'a>(env: NifEnv<'a>, args: &[NifTerm<'a>]) -> NifResult<NifTerm<'a>> {
+    unpack_context!(resource, args[0]);
+
+    let keyserver = resource.keyserver.read().unwrap().clone();
+
+    let workers = resource.usable_workers();
+    let mut clones: Vec<Context> = Vec::with_capacity(workers.len());
+    for worker in workers {
+        let clone = try_or_error!(worker.run(| context | settings::clone_context(context)), env);
+        clones.push(try_gpgme!(clone, env));
+    }
+
+    let clone_resource = try_or_error!(resource::wrap_contexts(clones), env);
+    *clone_resource.keyserver.write().unwrap() = keyserver;
+    clone_resource.kill_on_drop.store(resource.kill_on_drop.load(Ordering::SeqCst), Ordering::SeqCst);
+    *clone_resource.temporary_home_dir.lock().unwrap() = resource.temporary_home_dir.lock().unwrap().clone();
+
+    Ok((atoms::ok(), clone_resource).encode(env))
 }
 
 context_getter!(protocol, context, env, { protocol::protocol_to_nif(env, context.protocol()) });
@@ -39,29 +139,37 @@ context_getter!(text_mode, context, env, { context.text_mode().encode(env) });
 context_setter!(set_text_mode, context, env, yes, bool, { context.set_text_mode(yes) });
 context_getter!(armor, context, env, { context.armor().encode(env) });
 context_setter!(set_armor, context, env, yes, bool, { context.set_armor(yes) });
+context_getter!(include_certs, context, env, { context.include_certs().encode(env) });
+context_setter!(set_include_certs, context, env, count, i32, { context.set_include_certs(count) });
 
 pub fn get_flag<'a>(env: NifEnv<'a>, args: &[NifTerm<'a>]) -> NifResult<NifTerm<'a>> {
-    unpack_immutable_context!(context, args[0]);
+    unpack_context!(resource, args[0]);
 
     let name: String = try!(args[1].decode());
 
-    match context.get_flag(name) {
-        Ok(result) => Ok((atoms::ok(), String::from(result)).encode(env)),
-        Err(_) => Ok((atoms::error(), atoms::not_set()).encode(env))
-    }
+    resource.checkout().run_in_env(env, move | env, context | {
+        match context.get_flag(name) {
+            Ok(result) => Ok((atoms::ok(), String::from(result)).encode(env)),
+            Err(_) => Ok((atoms::error(), atoms::not_set()).encode(env))
+        }
+    })
 }
 
 pub fn set_flag<'a>(env: NifEnv<'a>, args: &[NifTerm<'a>]) -> NifResult<NifTerm<'a>> {
-    unpack_mutable_context!(context, args[0]);
+    unpack_context!(resource, args[0]);
 
     let name: String = try!(args[1].decode());
     let value: String = try!(args[2].decode());
 
-    try_gpgme!(context.set_flag(name, value), env);
+    resource.run_on_all(env, move | env, context | {
+        try_gpgme!(context.set_flag(name.as_str(), value.as_str()), env);
 
-    Ok(atoms::ok().encode(env))
+        Ok(atoms::ok().encode(env))
+    })
 }
 
+context_getter!(capabilities, context, env, { capabilities::capabilities_to_term(context, env) });
+
 context_getter!(engine_info, context, env, {
     match engine::engine_info_to_term(context.engine_info(), env) {
         Ok(result) => (atoms::ok(), result).encode(env),
@@ -69,134 +177,770 @@ context_getter!(engine_info, context, env, {
     }
 });
 
-context_setter!(set_engine_path, context, env, path, String, { try_gpgme!(context.set_engine_path(path), env) });
-context_setter!(set_engine_home_dir, context, env, home_dir, String, { try_gpgme!(context.set_engine_home_dir(home_dir), env) });
+context_setter!(set_engine_path, context, env, path, String, { try_gpgme!(context.set_engine_path(path.as_str()), env) });
+context_setter!(set_engine_home_dir, context, env, home_dir, String, { try_gpgme!(context.set_engine_home_dir(home_dir.as_str()), env) });
+
+pub fn keyserver<'a>(env: NifEnv<'a>, args: &[NifTerm<'a>]) -> NifResult<NifTerm<'a>> {
+    unpack_context!(resource, args[0]);
+
+    match *resource.keyserver.read().unwrap() {
+        Some(ref keyserver) => Ok(keyserver.encode(env)),
+        None => Ok(nil().encode(env))
+    }
+}
+
+pub fn set_kill_on_drop<'a>(env: NifEnv<'a>, args: &[NifTerm<'a>]) -> NifResult<NifTerm<'a>> {
+    unpack_context!(resource, args[0]);
+
+    let yes: bool = args[1].decode()?;
+
+    resource.kill_on_drop.store(yes, Ordering::SeqCst);
+
+    Ok(atoms::ok().encode(env))
+}
+
+pub fn set_keyserver<'a>(env: NifEnv<'a>, args: &[NifTerm<'a>]) -> NifResult<NifTerm<'a>> {
+    unpack_context!(resource, args[0]);
+
+    let keyserver: String = args[1].decode()?;
+
+    let home_dir = try_or_error!(resource.checkout().run(| context | options::keyserver_home_dir(context)), env);
+    try_or_error!(home_dir, env);
+
+    *resource.keyserver.write().unwrap() = Some(keyserver);
+
+    Ok(atoms::ok().encode(env))
+}
+
+context_getter!(sender, context, env, {
+    match string_or_null!(context.sender(), env) {
+        Ok(result) => result,
+        Err(_) => (atoms::error(), String::from("Could not decode sender to utf8")).encode(env)
+    }
+});
+
+pub fn set_sender<'a>(env: NifEnv<'a>, args: &[NifTerm<'a>]) -> NifResult<NifTerm<'a>> {
+    unpack_context!(resource, args[0]);
+
+    let sender: Option<String> = if args[1] == nil().encode(env) {
+        None
+    } else {
+        Some(args[1].decode()?)
+    };
+
+    resource.run_on_all(env, move | env, context | {
+        match sender {
+            Some(ref sender) => try_gpgme!(context.set_sender(sender.as_str()), env),
+            None => try_gpgme!(context.clear_sender(), env)
+        }
+
+        Ok(atoms::ok().encode(env))
+    })
+}
+
+context_getter!(key_list_mode, context, env, { key_list_mode::key_list_mode_to_term(context.key_list_mode(), env) });
+
+pub fn set_key_list_mode<'a>(env: NifEnv<'a>, args: &[NifTerm<'a>]) -> NifResult<NifTerm<'a>> {
+    unpack_context!(resource, args[0]);
+
+    let mode = key_list_mode::arg_to_key_list_mode(args[1].decode::<NifListIterator>()?)?;
+
+    resource.run_on_all(env, move | env, context | {
+        try_gpgme!(context.set_key_list_mode(mode), env);
+
+        Ok(atoms::ok().encode(env))
+    })
+}
+
+context_getter!(signers, context, env, {
+    context.signers()
+        .map(keys::wrap_key)
+        .collect::<Vec<ResourceArc<keys::KeyResource>>>()
+        .encode(env)
+});
+
+pub fn add_signer<'a>(env: NifEnv<'a>, args: &[NifTerm<'a>]) -> NifResult<NifTerm<'a>> {
+    unpack_context!(resource, args[0]);
+
+    let key_arc = try!(args[1].decode::<ResourceArc<keys::KeyResource>>());
+    let key: Key = key_arc.deref().key.clone();
+
+    resource.run_on_all(env, move | env, context | {
+        try_gpgme!(context.add_signer(&key), env);
+
+        Ok(atoms::ok().encode(env))
+    })
+}
+
+pub fn clear_signers<'a>(env: NifEnv<'a>, args: &[NifTerm<'a>]) -> NifResult<NifTerm<'a>> {
+    unpack_context!(resource, args[0]);
+
+    resource.run_on_all(env, | env, context | {
+        context.clear_signers();
+
+        Ok(atoms::ok().encode(env))
+    })
+}
 
 context_getter!(pinentry_mode, context, env, { pinentry_mode::pinentry_mode_to_term(context.pinentry_mode(), env) });
 
 pub fn set_pinentry_mode<'a>(env: NifEnv<'a>, args: &[NifTerm<'a>]) -> NifResult<NifTerm<'a>> {
-    unpack_mutable_context!(context, args[0]);
+    unpack_context!(resource, args[0]);
 
     let mode = pinentry_mode::arg_to_pinentry_mode(args[1])?;
 
-    try_gpgme!(context.set_pinentry_mode(mode), env);
+    resource.run_on_all(env, move | env, context | {
+        try_gpgme!(context.set_pinentry_mode(mode), env);
 
-    Ok(atoms::ok().encode(env))
+        Ok(atoms::ok().encode(env))
+    })
+}
+
+pub fn add_signature_notation<'a>(env: NifEnv<'a>, args: &[NifTerm<'a>]) -> NifResult<NifTerm<'a>> {
+    unpack_context!(resource, args[0]);
+
+    let name: String = args[1].decode()?;
+    let value: String = args[2].decode()?;
+    let flags = signature_notation_flags::arg_to_signature_notation_flags(args[3].decode::<NifListIterator>()?)?;
+
+    resource.run_on_all(env, move | env, context | {
+        try_gpgme!(context.add_signature_notation(name.as_str(), value.as_str(), flags), env);
+
+        Ok(atoms::ok().encode(env))
+    })
+}
+
+pub fn clear_signature_notations<'a>(env: NifEnv<'a>, args: &[NifTerm<'a>]) -> NifResult<NifTerm<'a>> {
+    unpack_context!(resource, args[0]);
+
+    resource.run_on_all(env, | env, context | {
+        context.clear_signature_notations();
+
+        Ok(atoms::ok().encode(env))
+    })
+}
+
+pub fn signature_notations<'a>(env: NifEnv<'a>, args: &[NifTerm<'a>]) -> NifResult<NifTerm<'a>> {
+    unpack_context!(resource, args[0]);
+
+    resource.checkout().run_in_env(env, | env, context | {
+        let mut notations: Vec<NifTerm> = Vec::new();
+        for notation in context.signature_notations().filter(| notation | notation.name().is_ok()) {
+            match transform_signature_notation(env, notation) {
+                Ok(notation) => notations.push(notation),
+                Err(_) => return Ok((atoms::error(), String::from("Could not decode notation to utf8")).encode(env))
+            }
+        }
+
+        Ok((atoms::ok(), notations).encode(env))
+    })
+}
+
+pub fn set_signature_policy_url<'a>(env: NifEnv<'a>, args: &[NifTerm<'a>]) -> NifResult<NifTerm<'a>> {
+    unpack_context!(resource, args[0]);
+
+    let url: String = args[1].decode()?;
+    let critical: bool = args[2].decode()?;
+
+    resource.run_on_all(env, move | env, context | {
+        try_gpgme!(context.add_signature_policy_url(url.as_str(), critical), env);
+
+        Ok(atoms::ok().encode(env))
+    })
 }
 
-pub fn import<'a>(env: NifEnv<'a>, args: &[NifTerm<'a>]) -> NifResult<NifTerm<'a>> {
-    unpack_mutable_context!(context, args[0]);
+context_getter!(signature_policy_url, context, env, {
+    match string_or_null!(context.signature_policy_url(), env) {
+        Ok(result) => result,
+        Err(_) => (atoms::error(), String::from("Could not decode policy url to utf8")).encode(env)
+    }
+});
+
+pub fn import_with_options<'a>(env: NifEnv<'a>, args: &[NifTerm<'a>]) -> NifResult<NifTerm<'a>> {
+    unpack_context!(resource, args[0]);
+
+    let data: Vec<u8> = args[1].decode::<NifBinary>()?.as_slice().to_vec();
+
+    let options = import_options::arg_to_import_options(args[2].decode::<NifListIterator>()?)?;
+
+    resource.checkout().run_in_env(env, move | env, context | {
+        let result = try_gpgme!(import_options::with_import_options(context, &options, | context | {
+            context.import(data)
+        }), env);
+
+        Ok((atoms::ok(), transform_import_result(env, result)).encode(env))
+    })
+}
+
+pub fn import_keys<'a>(env: NifEnv<'a>, args: &[NifTerm<'a>]) -> NifResult<NifTerm<'a>> {
+    unpack_context!(resource, args[0]);
+    unpack_key_list!(keys, args[1]);
+
+    keys::keys_not_empty(keys.len())?;
+
+    resource.checkout().run_in_env(env, move | env, context | {
+        let result = try_gpgme!(context.import_keys(&keys), env);
+
+        Ok((atoms::ok(), transform_import_result(env, result)).encode(env))
+    })
+}
+
+pub fn export_keys<'a>(env: NifEnv<'a>, args: &[NifTerm<'a>]) -> NifResult<NifTerm<'a>> {
+    unpack_context!(resource, args[0]);
+    unpack_key_list!(keys, args[1]);
+
+    keys::keys_not_empty(keys.len())?;
+
+    let (mode, passphrase) = export_mode::arg_to_export_mode(args[2].decode::<NifListIterator>()?)?;
+
+    resource.checkout().run_in_env(env, move | env, context | {
+        let mut data: Vec<u8> = Vec::new();
+        match passphrase {
+            Some(passphrase) => try_gpgme!(passphrase::with_passphrase(context, &passphrase, | context | {
+                context.export_keys(&keys, mode, &mut data)
+            }), env),
+            None => try_gpgme!(context.export_keys(&keys, mode, &mut data), env)
+        };
+
+        binary_context_result!(data, env)
+    })
+}
+
+pub fn locate_key<'a>(env: NifEnv<'a>, args: &[NifTerm<'a>]) -> NifResult<NifTerm<'a>> {
+    unpack_context!(resource, args[0]);
+
+    let email: String = args[1].decode()?;
+
+    resource.checkout().run_in_env(env, move | env, context | {
+        let key_list_mode = context.key_list_mode();
+        try_gpgme!(context.set_key_list_mode(gpgme::KEY_LIST_MODE_LOCAL | gpgme::KEY_LIST_MODE_EXTERN), env);
+
+        let found_keys = with_flag(context, "auto-key-locate", "clear,nodefault,wkd", | context | {
+            context.find_keys(vec![email])
+                .map(| keys | keys.filter_map(| key | key.ok()).collect::<Vec<Key>>())
+        });
+
+        try_gpgme!(context.set_key_list_mode(key_list_mode), env);
+        let found_keys = try_gpgme!(found_keys, env);
+
+        let mut keys: Vec<NifTerm> = Vec::new();
+        for key in found_keys.iter() {
+            match transform_key_details(env, key) {
+                Ok(key_details) => keys.push(key_details),
+                Err(_) => return Ok((atoms::error(), String::from("Could not decode key details to utf8")).encode(env))
+            }
+        }
+
+        Ok((atoms::ok(), keys).encode(env))
+    })
+}
+
+pub fn inspect_keys<'a>(env: NifEnv<'a>, args: &[NifTerm<'a>]) -> NifResult<NifTerm<'a>> {
+    unpack_context!(resource, args[0]);
+
+    let data: Vec<u8> = args[1].decode::<NifBinary>()?.as_slice().to_vec();
 
-    let data: String = try!(args[1].decode());
+    resource.checkout().run_in_env(env, move | env, context | {
+        let found_keys = try_gpgme!(context.read_keys(data.as_slice())
+            .map(| keys | keys.filter_map(| key | key.ok()).collect::<Vec<Key>>()), env);
 
-    let result = try_gpgme!(context.import(data), env);
+        let mut keys: Vec<NifTerm> = Vec::new();
+        for key in found_keys.iter() {
+            match transform_key_details(env, key) {
+                Ok(key_details) => keys.push(key_details),
+                Err(_) => return Ok((atoms::error(), String::from("Could not decode key details to utf8")).encode(env))
+            }
+        }
 
-    Ok((atoms::ok(), transform_import_result(env, result)).encode(env))
+        Ok((atoms::ok(), keys).encode(env))
+    })
+}
+
+pub fn key_details<'a>(env: NifEnv<'a>, args: &[NifTerm<'a>]) -> NifResult<NifTerm<'a>> {
+    let key_arc = try!(args[0].decode::<ResourceArc<keys::KeyResource>>());
+    let key_ref = key_arc.deref();
+
+    match transform_key_details(env, &key_ref.key) {
+        Ok(key_details) => Ok((atoms::ok(), key_details).encode(env)),
+        Err(_) => Ok((atoms::error(), String::from("Could not decode key details to utf8")).encode(env))
+    }
 }
 
 pub fn find_key<'a>(env: NifEnv<'a>, args: &[NifTerm<'a>]) -> NifResult<NifTerm<'a>> {
-    unpack_immutable_context!(context, args[0]);
+    unpack_context!(resource, args[0]);
 
     let fingerprint: String = try!(args[1].decode());
 
-    let result = try_gpgme!(context.find_key(fingerprint), env);
+    resource.checkout().run_in_env(env, move | env, context | {
+        let result = try_gpgme!(context.find_key(fingerprint), env);
 
-    Ok((atoms::ok(), keys::wrap_key(result)).encode(env))
+        Ok((atoms::ok(), keys::wrap_key(result)).encode(env))
+    })
 }
 
 pub fn encrypt_with_flags<'a>(env: NifEnv<'a>, args: &[NifTerm<'a>]) -> NifResult<NifTerm<'a>> {
-    unpack_mutable_context!(context, args[0]);
+    unpack_context!(resource, args[0]);
     unpack_key_list!(recipients, args[1]);
 
     keys::keys_not_empty(recipients.len())?;
 
-    let data: String = args[2].decode()?;
+    let data: Vec<u8> = args[2].decode::<NifBinary>()?.as_slice().to_vec();
 
     let flags: EncryptFlags = encrypt_flags::arg_to_protocol(args[3].decode::<NifListIterator>()?)?;
 
-    let mut cyphertext: Vec<u8> = Vec::new();
-    try_gpgme!(context.encrypt_with_flags(recipients, data, &mut cyphertext, flags), env);
+    resource.checkout().run_in_env(env, move | env, context | {
+        let mut cyphertext: Vec<u8> = Vec::new();
+        try_gpgme!(context.encrypt_with_flags(&recipients, data, &mut cyphertext, flags), env);
 
-    decode_context_result!(cyphertext, env)
+        binary_context_result!(cyphertext, env)
+    })
+}
+
+pub fn encrypt_symmetric_with_flags<'a>(env: NifEnv<'a>, args: &[NifTerm<'a>]) -> NifResult<NifTerm<'a>> {
+    unpack_context!(resource, args[0]);
+
+    let passphrase: String = args[1].decode()?;
+
+    unpack_key_list!(recipients, args[2]);
+
+    let data: Vec<u8> = args[3].decode::<NifBinary>()?.as_slice().to_vec();
+
+    let mut flags: EncryptFlags = encrypt_flags::arg_to_protocol(args[4].decode::<NifListIterator>()?)?;
+    flags.insert(gpgme::ENCRYPT_SYMMETRIC);
+
+    resource.checkout().run_in_env(env, move | env, context | {
+        let mut cyphertext: Vec<u8> = Vec::new();
+        try_gpgme!(passphrase::without_symmetric_key_cache(context, | context | {
+            passphrase::with_passphrase(context, &passphrase, | context | {
+                context.encrypt_with_flags(&recipients, data, &mut cyphertext, flags)
+            })
+        }), env);
+
+        binary_context_result!(cyphertext, env)
+    })
 }
 
 pub fn sign_and_encrypt_with_flags<'a>(env: NifEnv<'a>, args: &[NifTerm<'a>]) -> NifResult<NifTerm<'a>> {
-    unpack_mutable_context!(context, args[0]);
+    unpack_context!(resource, args[0]);
     unpack_key_list!(recipients, args[1]);
 
     keys::keys_not_empty(recipients.len())?;
 
-    let data: String = args[2].decode()?;
+    let data: Vec<u8> = args[2].decode::<NifBinary>()?.as_slice().to_vec();
 
     let flags: EncryptFlags = encrypt_flags::arg_to_protocol(args[3].decode::<NifListIterator>()?)?;
 
-    let mut cyphertext: Vec<u8> = Vec::new();
-    try_gpgme!(context.sign_and_encrypt_with_flags(recipients, data, &mut cyphertext, flags), env);
+    resource.checkout().run_in_env(env, move | env, context | {
+        let mut cyphertext: Vec<u8> = Vec::new();
+        try_gpgme!(context.sign_and_encrypt_with_flags(&recipients, data, &mut cyphertext, flags), env);
 
-    decode_context_result!(cyphertext, env)
+        binary_context_result!(cyphertext, env)
+    })
 }
 
 pub fn delete_key<'a>(env: NifEnv<'a>, args: &[NifTerm<'a>]) -> NifResult<NifTerm<'a>> {
-    unpack_mutable_context!(context, args[0]);
+    unpack_context!(resource, args[0]);
 
     let key_arc = try!(args[1].decode::<ResourceArc<keys::KeyResource>>());
-    let key_ref = key_arc.deref();
-    let key: &Key = &key_ref.key;
+    let key: Key = key_arc.deref().key.clone();
 
-    try_gpgme!(context.delete_key(key), env);
+    resource.checkout().run_in_env(env, move | env, context | {
+        try_gpgme!(context.delete_key(&key), env);
 
-    Ok(atoms::ok().encode(env))
+        Ok(atoms::ok().encode(env))
+    })
 }
 
 pub fn delete_secret_key<'a>(env: NifEnv<'a>, args: &[NifTerm<'a>]) -> NifResult<NifTerm<'a>> {
-    unpack_mutable_context!(context, args[0]);
+    unpack_context!(resource, args[0]);
 
     let key_arc = try!(args[1].decode::<ResourceArc<keys::KeyResource>>());
-    let key_ref = key_arc.deref();
-    let key: &Key = &key_ref.key;
+    let key: Key = key_arc.deref().key.clone();
 
-    try_gpgme!(context.delete_secret_key(key), env);
+    resource.checkout().run_in_env(env, move | env, context | {
+        try_gpgme!(context.delete_secret_key(&key), env);
 
-    Ok(atoms::ok().encode(env))
+        Ok(atoms::ok().encode(env))
+    })
 }
 
 pub fn decrypt<'a>(env: NifEnv<'a>, args: &[NifTerm<'a>]) -> NifResult<NifTerm<'a>> {
-    unpack_mutable_context!(context, args[0]);
+    unpack_context!(resource, args[0]);
+
+    let cyphertext: Vec<u8> = args[1].decode::<NifBinary>()?.as_slice().to_vec();
+
+    resource.checkout().run_in_env(env, move | env, context | {
+        let mut cleartext: Vec<u8> = Vec::new();
+
+        try_gpgme!(context.decrypt(cyphertext, &mut cleartext), env);
+
+        binary_context_result!(cleartext, env)
+    })
+}
+
+fn audit_log_error<'a>(env: NifEnv<'a>, context: &mut Context, error: gpgme::Error, flags: gpgme::AuditLogFlags) -> NifTerm<'a> {
+    let mut log: Vec<u8> = Vec::new();
+
+    match context.get_audit_log(&mut log, flags) {
+        Ok(_) => (atoms::error(), (error.description().into_owned(), helpers::binary_to_term(env, &log))).encode(env),
+        Err(_) => (atoms::error(), error.description().into_owned()).encode(env)
+    }
+}
+
+pub fn audit_log<'a>(env: NifEnv<'a>, args: &[NifTerm<'a>]) -> NifResult<NifTerm<'a>> {
+    unpack_context!(resource, args[0]);
+
+    let flags = audit_log_format::arg_to_audit_log_flags(args[1])?;
+
+    for worker in resource.workers.iter() {
+        let log = try_or_error!(worker.run_for_last_caller(env, move | context | {
+            let mut log: Vec<u8> = Vec::new();
+            context.get_audit_log(&mut log, flags)
+                .map(| _ | log)
+                .map_err(| error | error.description().into_owned())
+        }), env);
+
+        if let Some(log) = log {
+            let log = try_or_error!(log, env);
+            return Ok((atoms::ok(), helpers::binary_to_term(env, &log)).encode(env));
+        }
+    }
+
+    Ok((atoms::error(), String::from("No operation of this process to take the audit log of")).encode(env))
+}
+
+pub fn decrypt_with_audit_log<'a>(env: NifEnv<'a>, args: &[NifTerm<'a>]) -> NifResult<NifTerm<'a>> {
+    unpack_context!(resource, args[0]);
+
+    let cyphertext: Vec<u8> = args[1].decode::<NifBinary>()?.as_slice().to_vec();
+
+    let flags = audit_log_format::arg_to_audit_log_flags(args[2])?;
+
+    resource.checkout().run_in_env(env, move | env, context | {
+        let mut cleartext: Vec<u8> = Vec::new();
+        let mut log: Vec<u8> = Vec::new();
+
+        if let Err(error) = context.decrypt(cyphertext, &mut cleartext) {
+            return Ok(audit_log_error(env, context, error, flags));
+        }
+        try_gpgme!(context.get_audit_log(&mut log, flags), env);
+
+        Ok((atoms::ok(), (helpers::binary_to_term(env, &cleartext), helpers::binary_to_term(env, &log))).encode(env))
+    })
+}
+
+pub fn decrypt_with_passphrase<'a>(env: NifEnv<'a>, args: &[NifTerm<'a>]) -> NifResult<NifTerm<'a>> {
+    unpack_context!(resource, args[0]);
+
+    let passphrase: String = args[1].decode()?;
+
+    let cyphertext: Vec<u8> = args[2].decode::<NifBinary>()?.as_slice().to_vec();
+
+    resource.checkout().run_in_env(env, move | env, context | {
+        let mut cleartext: Vec<u8> = Vec::new();
+
+        try_gpgme!(passphrase::without_symmetric_key_cache(context, | context | {
+            passphrase::with_passphrase(context, &passphrase, | context | {
+                context.decrypt(cyphertext, &mut cleartext)
+            })
+        }), env);
 
-    let cyphertext: String = try!(args[1].decode::<String>());//.into_bytes();
+        binary_context_result!(cleartext, env)
+    })
+}
+
+pub fn decrypt_export_session_key<'a>(env: NifEnv<'a>, args: &[NifTerm<'a>]) -> NifResult<NifTerm<'a>> {
+    unpack_context!(resource, args[0]);
+
+    let cyphertext: Vec<u8> = args[1].decode::<NifBinary>()?.as_slice().to_vec();
+
+    resource.checkout().run_in_env(env, move | env, context | {
+        let mut cleartext: Vec<u8> = Vec::new();
 
-    let mut cleartext: Vec<u8> = Vec::new();
+        let result = try_gpgme!(with_flag(context, "export-session-key", "1", | context | {
+            context.decrypt(cyphertext, &mut cleartext)
+        }), env);
 
-    try_gpgme!(context.decrypt(cyphertext, &mut cleartext), env);
+        let decryption_result = match transform_decryption_result(env, result) {
+            Ok(nif_result) => nif_result,
+            Err(_) => return Ok((atoms::error(), String::from("Could not decode decryption result to utf8")).encode(env))
+        };
 
-    decode_context_result!(cleartext, env)
+        Ok((atoms::ok(), (helpers::binary_to_term(env, &cleartext), decryption_result)).encode(env))
+    })
+}
+
+pub fn decrypt_with_session_key<'a>(env: NifEnv<'a>, args: &[NifTerm<'a>]) -> NifResult<NifTerm<'a>> {
+    unpack_context!(resource, args[0]);
+
+    let session_key: String = args[1].decode()?;
+
+    let cyphertext: Vec<u8> = args[2].decode::<NifBinary>()?.as_slice().to_vec();
+
+    resource.checkout().run_in_env(env, move | env, context | {
+        let mut cleartext: Vec<u8> = Vec::new();
+
+        try_gpgme!(with_flag(context, "override-session-key", &session_key, | context | {
+            context.decrypt(cyphertext, &mut cleartext)
+        }), env);
+
+        binary_context_result!(cleartext, env)
+    })
 }
 
 pub fn sign_with_mode<'a>(env: NifEnv<'a>, args: &[NifTerm<'a>]) -> NifResult<NifTerm<'a>> {
-    unpack_mutable_context!(context, args[0]);
+    unpack_context!(resource, args[0]);
 
     let mode = sign_mode::arg_to_sign_mode(args[1])?;
 
-    let data: String = args[2].decode()?;
+    let data: Vec<u8> = args[2].decode::<NifBinary>()?.as_slice().to_vec();
 
-    let mut signature: Vec<u8> = Vec::new();
+    resource.checkout().run_in_env(env, move | env, context | {
+        let mut signature: Vec<u8> = Vec::new();
 
-    try_gpgme!(context.sign(mode, data, &mut signature), env);
+        try_gpgme!(context.sign(mode, data, &mut signature), env);
 
-    decode_context_result!(signature, env)
+        binary_context_result!(signature, env)
+    })
 }
 
 pub fn verify_opaque<'a>(env: NifEnv<'a>, args: &[NifTerm<'a>]) -> NifResult<NifTerm<'a>> {
-    unpack_mutable_context!(context, args[0]);
+    unpack_context!(resource, args[0]);
+
+    let signature: Vec<u8> = args[1].decode::<NifBinary>()?.as_slice().to_vec();
 
-    let signature: String = args[1].decode()?;
+    let data: Vec<u8> = args[2].decode::<NifBinary>()?.as_slice().to_vec();
+
+    resource.checkout().run_in_env(env, move | env, context | {
+        let result = try_gpgme!(context.verify_opaque(signature, data), env);
+
+        match transform_verification_result(env, result, context) {
+            Ok(nif_result) => Ok((atoms::ok(), nif_result).encode(env)),
+            Err(_) => Ok((atoms::error(), String::from("Could not decode cyphertext to utf8")).encode(env))
+        }
+    })
+}
 
-    let data: String = args[2].decode()?;
+pub fn verify_opaque_with_audit_log<'a>(env: NifEnv<'a>, args: &[NifTerm<'a>]) -> NifResult<NifTerm<'a>> {
+    unpack_context!(resource, args[0]);
 
-    let result = try_gpgme!(context.verify_opaque(signature, data), env);
+    let signature: Vec<u8> = args[1].decode::<NifBinary>()?.as_slice().to_vec();
 
-    match transform_verification_result(env, result) {
-        Ok(nif_result) => Ok((atoms::ok(), nif_result).encode(env)),
-        Err(_) => Ok((atoms::error(), String::from("Could not decode cyphertext to utf8")).encode(env))
+    let data: Vec<u8> = args[2].decode::<NifBinary>()?.as_slice().to_vec();
+
+    let flags = audit_log_format::arg_to_audit_log_flags(args[3])?;
+
+    resource.checkout().run_in_env(env, move | env, context | {
+        let result = match context.verify_opaque(signature, data) {
+            Ok(result) => result,
+            Err(error) => return Ok(audit_log_error(env, context, error, flags))
+        };
+
+        let mut log: Vec<u8> = Vec::new();
+        try_gpgme!(context.get_audit_log(&mut log, flags), env);
+
+        match transform_verification_result(env, result, context) {
+            Ok(nif_result) => Ok((atoms::ok(), (nif_result, helpers::binary_to_term(env, &log))).encode(env)),
+            Err(_) => Ok((atoms::error(), String::from("Could not decode cyphertext to utf8")).encode(env))
+        }
+    })
+}
+
+pub fn generate_certificate_request<'a>(env: NifEnv<'a>, args: &[NifTerm<'a>]) -> NifResult<NifTerm<'a>> {
+    unpack_context!(resource, args[0]);
+
+    let parameters: String = args[1].decode()?;
+
+    let passphrase: Option<String> = if args[2] == nil().encode(env) {
+        None
+    } else {
+        Some(args[2].decode()?)
+    };
+
+    resource.checkout().run_in_env(env, move | env, context | {
+        let mut request: Vec<u8> = Vec::new();
+
+        match passphrase {
+            Some(passphrase) => try_gpgme!(passphrase::with_passphrase(context, &passphrase, | context | {
+                context.generate_key(parameters, Some(&mut request), None::<&mut Vec<u8>>)
+            }), env),
+            None => try_gpgme!(context.generate_key(parameters, Some(&mut request), None::<&mut Vec<u8>>), env)
+        };
+
+        binary_context_result!(request, env)
+    })
+}
+
+pub fn assuan_transact_with_inquiries<'a>(env: NifEnv<'a>, args: &[NifTerm<'a>]) -> NifResult<NifTerm<'a>> {
+    unpack_context!(resource, args[0]);
+
+    let command: String = args[1].decode()?;
+
+    let answers: Vec<(String, Vec<u8>)> = args[2].decode::<Vec<(String, NifBinary)>>()?
+        .into_iter()
+        .map(| (name, answer) | (name, answer.as_slice().to_vec()))
+        .collect();
+
+    resource.checkout().run_in_env(env, move | env, context | {
+        let mut data: Vec<u8> = Vec::new();
+        let mut status: Vec<(String, Option<String>)> = Vec::new();
+        let mut inquiries: Vec<(String, Option<String>)> = Vec::new();
+
+        try_gpgme!(context.assuan_transact_with_callbacks(command,
+            | line: &[u8] | {
+                data.extend_from_slice(line);
+                Ok(())
+            },
+            | name: &str, inquiry_args: Option<&str>, out: &mut Write | {
+                inquiries.push((String::from(name), inquiry_args.map(String::from)));
+                match answers.iter().find(| &&(ref answer_name, _) | answer_name == name) {
+                    Some(&(_, ref answer)) => {
+                        out.write_all(answer)?;
+                        Ok(())
+                    },
+                    None => Err(gpgme::Error::CANCELED)
+                }
+            },
+            | keyword: &str, status_args: Option<&str> | {
+                status.push((String::from(keyword), status_args.map(String::from)));
+                Ok(())
+            }), env);
+
+        Ok((atoms::ok(), transform_assuan_result(env, &data, &status, &inquiries)).encode(env))
+    })
+}
+
+fn with_keyserver<F, R>(home_dir: Result<String, String>, keyserver: &Option<String>, operation: F) -> Result<R, String>
+    where F: FnOnce() -> R {
+    match *keyserver {
+        Some(ref keyserver) => {
+            let home_dir = home_dir?;
+            gpgconf::with_option(&home_dir, "dirmngr", "keyserver", &gpgconf::escape_string_value(keyserver), operation)
+        },
+        None => Ok(operation())
     }
 }
+
+pub fn receive_keys<'a>(env: NifEnv<'a>, args: &[NifTerm<'a>]) -> NifResult<NifTerm<'a>> {
+    unpack_context!(resource, args[0]);
+
+    let key_ids: Vec<String> = args[1].decode()?;
+
+    keys::keys_not_empty(key_ids.len())?;
+
+    let keyserver = resource.keyserver.read().unwrap().clone();
+
+    resource.checkout().run_in_env(env, move | env, context | {
+        let key_list_mode = context.key_list_mode();
+        try_gpgme!(context.set_key_list_mode(gpgme::KEY_LIST_MODE_EXTERN), env);
+        let home_dir = options::keyserver_home_dir(context);
+        let found_keys = with_keyserver(home_dir, &keyserver, || {
+            context.find_keys(key_ids)
+                .map(| keys | keys.filter_map(| key | key.ok()).collect::<Vec<Key>>())
+        });
+        try_gpgme!(context.set_key_list_mode(key_list_mode), env);
+        let found_keys = try_gpgme!(try_or_error!(found_keys, env), env);
+
+        let result = try_gpgme!(context.import_keys(&found_keys), env);
+
+        Ok((atoms::ok(), transform_import_result(env, result)).encode(env))
+    })
+}
+
+pub fn send_keys<'a>(env: NifEnv<'a>, args: &[NifTerm<'a>]) -> NifResult<NifTerm<'a>> {
+    unpack_context!(resource, args[0]);
+    unpack_key_list!(keys, args[1]);
+
+    keys::keys_not_empty(keys.len())?;
+
+    let keyserver = resource.keyserver.read().unwrap().clone();
+
+    resource.checkout().run_in_env(env, move | env, context | {
+        let home_dir = options::keyserver_home_dir(context);
+        let result = try_or_error!(with_keyserver(home_dir, &keyserver, || {
+            context.export_keys_extern(&keys, gpgme::ExportMode::empty())
+        }), env);
+        try_gpgme!(result, env);
+
+        Ok(atoms::ok().encode(env))
+    })
+}
+
+fn context_home_dir(resource: &resource::ContextNifResource) -> Result<Option<String>, String> {
+    resource.checkout().run(| context | gpgconf::home_dir(context))
+}
+
+pub fn gpgconf_components<'a>(env: NifEnv<'a>, args: &[NifTerm<'a>]) -> NifResult<NifTerm<'a>> {
+    unpack_context!(resource, args[0]);
+
+    let home_dir = try_or_error!(context_home_dir(&resource), env);
+    let home_dir = home_dir.as_ref().map(String::as_str);
+
+    let components = try_or_error!(gpgconf::list_components(home_dir), env);
+
+    let mut terms: Vec<NifTerm<'a>> = Vec::new();
+    for component in components.iter() {
+        let options = try_or_error!(gpgconf::list_options(home_dir, &component.name), env);
+        terms.push(gpgconf::component_to_term(env, component, &options));
+    }
+
+    Ok((atoms::ok(), terms).encode(env))
+}
+
+pub fn gpgconf_change_option<'a>(env: NifEnv<'a>, args: &[NifTerm<'a>]) -> NifResult<NifTerm<'a>> {
+    unpack_context!(resource, args[0]);
+
+    let component: String = args[1].decode()?;
+    let option: String = args[2].decode()?;
+    let value: Option<String> = match args[3].get_type() {
+        TermType::Binary => Some(gpgconf::escape_string_value(&args[3].decode::<String>()?)),
+        TermType::Number => Some(args[3].decode::<i64>()?.to_string()),
+        TermType::Atom if args[3] == nil().encode(env) => None,
+        TermType::Atom => match args[3].decode::<bool>()? {
+            true => Some(String::from("1")),
+            false => None
+        },
+        _ => return Err(NifError::BadArg)
+    };
+
+    let home_dir = try_or_error!(context_home_dir(&resource), env);
+
+    try_or_error!(gpgconf::change_option(home_dir.as_ref().map(String::as_str), &component, &option, value.as_ref().map(String::as_str)), env);
+
+    Ok(atoms::ok().encode(env))
+}
+
+pub fn agent_context<'a>(env: NifEnv<'a>, args: &[NifTerm<'a>]) -> NifResult<NifTerm<'a>> {
+    unpack_context!(resource, args[0]);
+
+    let home_dir = try_or_error!(context_home_dir(&resource), env);
+    let home_dir = home_dir.as_ref().map(String::as_str);
+
+    try_or_error!(gpgconf::launch(home_dir, "gpg-agent"), env);
+    let socket = try_or_error!(gpgconf::agent_socket(home_dir), env);
+
+    let mut context = try_gpgme!(Context::from_protocol(gpgme::Protocol::Assuan), env);
+    try_gpgme!(context.set_engine_path(socket), env);
+
+    let agent_resource = try_or_error!(resource::wrap_context(context), env);
+
+    Ok((atoms::ok(), agent_resource).encode(env))
+}
+
+pub fn gpgconf_control<'a>(env: NifEnv<'a>, args: &[NifTerm<'a>]) -> NifResult<NifTerm<'a>> {
+    unpack_context!(resource, args[0]);
+
+    let action = match args[1].atom_to_string()?.as_ref() {
+        "launch" => "launch",
+        "reload" => "reload",
+        "kill" => "kill",
+        _ => return Err(NifError::BadArg)
+    };
+    let component: String = args[2].decode()?;
+
+    let home_dir = try_or_error!(context_home_dir(&resource), env);
+
+    try_or_error!(gpgconf::control(home_dir.as_ref().map(String::as_str), action, &component), env);
+
+    Ok(atoms::ok().encode(env))
+}
@@ -0,0 +1,111 @@
+use rustler::{NifTerm, NifError};
+use rustler::types::list::NifListIterator;
+use rustler::types::tuple;
+use gpgme::Context;
+use pinentry_mode;
+use key_list_mode;
+use keys;
+use rustler::resource::ResourceArc;
+
+pub struct ContextOptions {
+    pub keyserver: Option<String>,
+    pub pool_size: usize,
+    pub kill_on_drop: bool
+}
+
+fn invalid_value(name: &str) -> String {
+    format!("Invalid value for option {}", name)
+}
+
+fn decode_option<'a, T: ::rustler::NifDecoder<'a>>(name: &str, value: NifTerm<'a>) -> Result<T, String> {
+    value.decode::<T>().map_err(| _ | invalid_value(name))
+}
+
+fn gpgme_error(name: &str, error: ::gpgme::Error) -> String {
+    format!("{}: {}", invalid_value(name), error.description())
+}
+
+pub fn keyserver_home_dir(context: &Context) -> Result<String, String> {
+    match context.engine_info().home_dir() {
+        Ok(home_dir) => Ok(String::from(home_dir)),
+        Err(_) => Err(String::from("A keyserver can only be used by a context with an explicit home dir, see set_engine_home_dir/2"))
+    }
+}
+
+pub fn apply_options<'a>(context: &mut Context, options: NifListIterator<'a>) -> Result<ContextOptions, String> {
+    let mut context_options = ContextOptions {
+        keyserver: None,
+        pool_size: 1,
+        kill_on_drop: false
+    };
+
+    for option in options {
+        let (name, value) = match tuple::get_tuple(option) {
+            Ok(ref option_tuple) if option_tuple.len() == 2 => match option_tuple[0].atom_to_string() {
+                Ok(name) => (name, option_tuple[1]),
+                Err(_) => return Err(String::from("Options must be a keyword list"))
+            },
+            _ => return Err(String::from("Options must be a keyword list"))
+        };
+
+        apply_option(context, &mut context_options, &name, value)?;
+    }
+
+    if context_options.keyserver.is_some() {
+        keyserver_home_dir(context)?;
+    }
+
+    Ok(context_options)
+}
+
+fn apply_option<'a>(context: &mut Context, context_options: &mut ContextOptions, name: &str, value: NifTerm<'a>) -> Result<(), String> {
+    match name {
+        "armor" => context.set_armor(decode_option(name, value)?),
+        "text_mode" => context.set_text_mode(decode_option(name, value)?),
+        "offline" => context.set_offline(decode_option(name, value)?),
+        "include_certs" => context.set_include_certs(decode_option(name, value)?),
+        "engine_path" => {
+            let path: String = decode_option(name, value)?;
+            context.set_engine_path(path).map_err(| error | gpgme_error(name, error))?
+        },
+        "engine_home_dir" => {
+            let home_dir: String = decode_option(name, value)?;
+            context.set_engine_home_dir(home_dir).map_err(| error | gpgme_error(name, error))?
+        },
+        "pinentry_mode" => {
+            let mode = pinentry_mode::arg_to_pinentry_mode(value).map_err(| _: NifError | invalid_value(name))?;
+            context.set_pinentry_mode(mode).map_err(| error | gpgme_error(name, error))?
+        },
+        "key_list_mode" => {
+            let mode = value.decode::<NifListIterator>()
+                .and_then(key_list_mode::arg_to_key_list_mode)
+                .map_err(| _: NifError | invalid_value(name))?;
+            context.set_key_list_mode(mode).map_err(| error | gpgme_error(name, error))?
+        },
+        "signers" => {
+            let signers: Vec<ResourceArc<keys::KeyResource>> = decode_option(name, value)?;
+            for signer in signers.iter() {
+                context.add_signer(&signer.key).map_err(| error | gpgme_error(name, error))?
+            }
+        },
+        "flags" => {
+            let flags: Vec<(String, String)> = decode_option(name, value)?;
+            for (flag_name, flag_value) in flags {
+                context.set_flag(flag_name, flag_value).map_err(| error | gpgme_error(name, error))?
+            }
+        },
+        "sender" => {
+            let sender: String = decode_option(name, value)?;
+            context.set_sender(sender).map_err(| error | gpgme_error(name, error))?
+        },
+        "keyserver" => context_options.keyserver = Some(decode_option(name, value)?),
+        "pool_size" => match decode_option::<u32>(name, value)? {
+            0 => return Err(invalid_value(name)),
+            pool_size => context_options.pool_size = pool_size as usize
+        },
+        "kill_on_drop" => context_options.kill_on_drop = decode_option(name, value)?,
+        _ => return Err(format!("Unknown option {}", name))
+    }
+
+    Ok(())
+}
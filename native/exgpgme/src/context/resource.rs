@@ -1,29 +1,106 @@
+use rustler::{NifEnv, NifTerm, NifResult, NifEncoder};
 use rustler::resource::ResourceArc;
-use std::sync::{Arc, RwLock};
+use std::sync::{Arc, Mutex, RwLock};
+use std::sync::atomic::{AtomicBool, Ordering};
 use gpgme::Context;
+use context::worker::ContextWorker;
+use context::settings;
+use gpgconf;
+use home_dir::TemporaryHomeDir;
 
 pub struct ContextNifResource {
-    pub context: Arc<RwLock<Context>>
+    pub workers: Vec<ContextWorker>,
+    pub keyserver: RwLock<Option<String>>,
+    pub kill_on_drop: AtomicBool,
+    pub temporary_home_dir: Mutex<Option<Arc<TemporaryHomeDir>>>
 }
-unsafe impl Send for ContextNifResource {}
-unsafe impl Sync for ContextNifResource {}
 
-pub fn wrap_context(context: Context) -> ResourceArc<ContextNifResource> {
-    ResourceArc::new(ContextNifResource{
-        context: Arc::new(RwLock::new(context))
-    })
+impl ContextNifResource {
+    pub fn usable_workers(&self) -> Vec<&ContextWorker> {
+        self.workers.iter()
+            .filter(| worker | worker.is_usable())
+            .collect()
+    }
+
+    pub fn checkout(&self) -> &ContextWorker {
+        self.workers.iter()
+            .filter(| worker | worker.is_usable())
+            .min_by_key(| worker | worker.pending())
+            .unwrap()
+    }
+
+    pub fn run_on_all<'a, F>(&self, env: NifEnv<'a>, operation: F) -> NifResult<NifTerm<'a>>
+        where F: for<'b> Fn(NifEnv<'b>, &mut Context) -> NifResult<NifTerm<'b>> + Send + Sync + 'static {
+        let operation = Arc::new(operation);
+        let ok = ::context::helpers::atoms::ok().encode(env);
+
+        let workers = self.usable_workers();
+        let (first, others) = match workers.split_first() {
+            Some(workers) => workers,
+            None => return Ok(ok)
+        };
+
+        let first_operation = operation.clone();
+        let result = first.run_in_env(env, move | env, context | first_operation(env, context))?;
+        if result != ok {
+            return Ok(result);
+        }
+
+        for worker in others.iter() {
+            let operation = operation.clone();
+            match worker.run_in_env(env, move | env, context | operation(env, context)) {
+                Ok(ref result) if *result == ok => (),
+                _ => {
+                    if let Err(error) = resync(first, worker) {
+                        worker.retire();
+                        return Ok((::context::helpers::atoms::error(), error).encode(env));
+                    }
+                }
+            }
+        }
+
+        Ok(ok)
+    }
 }
 
-macro_rules! unpack_immutable_context {
-    ($context:ident, $arg:expr) => (
-        let context_arc: $crate::rustler::resource::ResourceArc<::context::resource::ContextNifResource> = $arg.decode()?;
-        let $context = context_arc.deref().context.read().unwrap();
-    );
+fn resync(source: &ContextWorker, target: &ContextWorker) -> Result<(), String> {
+    let clone = source.run(| context | settings::clone_context(context).map_err(| error | error.description().into_owned()))??;
+
+    target.run(move | context | *context = clone)
+}
+
+impl Drop for ContextNifResource {
+    fn drop(&mut self) {
+        if self.kill_on_drop.load(Ordering::SeqCst) {
+            self.checkout().run_detached(| context | {
+                if let Some(home_dir) = gpgconf::home_dir(context) {
+                    let _ = gpgconf::control(Some(&home_dir), "kill", "all");
+                }
+            });
+        }
+    }
+}
+
+pub fn wrap_context(context: Context) -> Result<ResourceArc<ContextNifResource>, String> {
+    wrap_contexts(vec![context])
+}
+
+pub fn wrap_contexts(contexts: Vec<Context>) -> Result<ResourceArc<ContextNifResource>, String> {
+    let mut workers: Vec<ContextWorker> = Vec::with_capacity(contexts.len());
+    for context in contexts {
+        workers.push(ContextWorker::spawn(context)?);
+    }
+
+    Ok(ResourceArc::new(ContextNifResource{
+        workers: workers,
+        keyserver: RwLock::new(None),
+        kill_on_drop: AtomicBool::new(false),
+        temporary_home_dir: Mutex::new(None)
+    }))
 }
 
-macro_rules! unpack_mutable_context {
-    ($context:ident, $arg:expr) => (
-        let context_arc: $crate::rustler::resource::ResourceArc<::context::resource::ContextNifResource> = $arg.decode()?;
-        let mut $context = context_arc.deref().context.write().unwrap();
+macro_rules! unpack_context {
+    ($resource:ident, $arg:expr) => (
+        let $resource: $crate::rustler::resource::ResourceArc<::context::resource::ContextNifResource> = $arg.decode()?;
     );
 }
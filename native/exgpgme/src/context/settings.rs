@@ -0,0 +1,133 @@
+use rustler::{NifEnv, NifTerm, NifEncoder};
+use rustler::types::map;
+use rustler::types::atom::nil;
+use gpgme;
+use gpgme::Context;
+use std::str::Utf8Error;
+use protocol;
+use pinentry_mode;
+use key_list_mode;
+use keys;
+
+mod atoms {
+    rustler_atoms! {
+        atom protocol;
+        atom armor;
+        atom text_mode;
+        atom offline;
+        atom include_certs;
+        atom pinentry_mode;
+        atom engine_path;
+        atom engine_home_dir;
+        atom flags;
+        atom key_list_mode;
+        atom signers;
+        atom sender;
+        atom keyserver;
+        atom pool_size;
+        atom kill_on_drop;
+    }
+}
+
+pub const FLAG_NAMES: &'static [&'static str] = &[
+    "redraw",
+    "full-status",
+    "raw-description",
+    "export-session-key",
+    "override-session-key",
+    "auto-key-retrieve",
+    "auto-key-locate",
+    "request-origin",
+    "no-symkey-cache",
+    "ignore-mdc-error",
+    "trust-model",
+    "import-options",
+    "import-filter",
+    "key-origin",
+];
+
+fn flags(context: &Context) -> Vec<(String, String)> {
+    FLAG_NAMES.iter()
+        .filter_map(| name | match context.get_flag(*name) {
+            Ok(value) if !value.is_empty() => Some((String::from(*name), String::from(value))),
+            _ => None
+        })
+        .collect()
+}
+
+pub fn settings_to_term<'a>(env: NifEnv<'a>, context: &Context, pool_size: usize, keyserver: &Option<String>, kill_on_drop: bool) -> Result<NifTerm<'a>, Utf8Error> {
+    let engine_info = context.engine_info();
+    let signers: Vec<NifTerm<'a>> = context.signers()
+        .map(| key | keys::wrap_key(key).encode(env))
+        .collect();
+    let keyserver = match *keyserver {
+        Some(ref keyserver) => keyserver.encode(env),
+        None => nil().encode(env)
+    };
+
+    Ok(
+        map::map_new(env)
+            .map_put(atoms::protocol().encode(env), protocol::protocol_to_nif(env, context.protocol())).ok().unwrap()
+            .map_put(atoms::armor().encode(env), context.armor().encode(env)).ok().unwrap()
+            .map_put(atoms::text_mode().encode(env), context.text_mode().encode(env)).ok().unwrap()
+            .map_put(atoms::offline().encode(env), context.offline().encode(env)).ok().unwrap()
+            .map_put(atoms::include_certs().encode(env), context.include_certs().encode(env)).ok().unwrap()
+            .map_put(atoms::pinentry_mode().encode(env), pinentry_mode::pinentry_mode_to_term(context.pinentry_mode(), env)).ok().unwrap()
+            .map_put(atoms::engine_path().encode(env), string_or_null!(engine_info.path(), env)?).ok().unwrap()
+            .map_put(atoms::engine_home_dir().encode(env), string_or_null!(engine_info.home_dir(), env)?).ok().unwrap()
+            .map_put(atoms::flags().encode(env), flags(context).encode(env)).ok().unwrap()
+            .map_put(atoms::key_list_mode().encode(env), key_list_mode::key_list_mode_to_term(context.key_list_mode(), env)).ok().unwrap()
+            .map_put(atoms::signers().encode(env), signers.encode(env)).ok().unwrap()
+            .map_put(atoms::sender().encode(env), string_or_null!(context.sender(), env)?).ok().unwrap()
+            .map_put(atoms::keyserver().encode(env), keyserver).ok().unwrap()
+            .map_put(atoms::pool_size().encode(env), (pool_size as u32).encode(env)).ok().unwrap()
+            .map_put(atoms::kill_on_drop().encode(env), kill_on_drop.encode(env)).ok().unwrap()
+    )
+}
+
+pub fn engine_context(context: &Context) -> gpgme::Result<Context> {
+    let mut engine_context = Context::from_protocol(context.protocol())?;
+
+    let engine_info = context.engine_info();
+    if let Ok(path) = engine_info.path() {
+        engine_context.set_engine_path(path)?;
+    }
+    if let Ok(home_dir) = engine_info.home_dir() {
+        engine_context.set_engine_home_dir(home_dir)?;
+    }
+
+    Ok(engine_context)
+}
+
+pub fn clone_context(context: &Context) -> gpgme::Result<Context> {
+    let mut clone = engine_context(context)?;
+
+    clone.set_armor(context.armor());
+    clone.set_text_mode(context.text_mode());
+    clone.set_offline(context.offline());
+    clone.set_include_certs(context.include_certs());
+    clone.set_pinentry_mode(context.pinentry_mode())?;
+    clone.set_key_list_mode(context.key_list_mode())?;
+
+    for (name, value) in flags(context) {
+        clone.set_flag(name, value)?;
+    }
+
+    for key in context.signers() {
+        clone.add_signer(&key)?;
+    }
+
+    if let Ok(sender) = context.sender() {
+        clone.set_sender(sender)?;
+    }
+
+    for notation in context.signature_notations() {
+        match (notation.name(), notation.value()) {
+            (Ok(name), Ok(value)) => clone.add_signature_notation(name, value, notation.flags())?,
+            (Err(None), Ok(url)) => clone.add_signature_policy_url(url, notation.is_critical())?,
+            _ => ()
+        }
+    }
+
+    Ok(clone)
+}
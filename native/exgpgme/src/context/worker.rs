@@ -0,0 +1,178 @@
+use rustler::{NifEnv, NifTerm, NifResult, NifEncoder};
+use rustler::env::{OwnedEnv, SavedTerm};
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::mpsc::{channel, Sender};
+use std::thread;
+use gpgme::Context;
+
+trait Command {
+    fn call(self: Box<Self>, context: &mut Context);
+}
+
+impl<F: FnOnce(&mut Context)> Command for F {
+    fn call(self: Box<Self>, context: &mut Context) {
+        (*self)(context)
+    }
+}
+
+struct PendingGuard<'a> {
+    pending: &'a AtomicUsize
+}
+
+impl<'a> PendingGuard<'a> {
+    fn new(pending: &'a AtomicUsize) -> PendingGuard<'a> {
+        pending.fetch_add(1, Ordering::SeqCst);
+        PendingGuard {
+            pending: pending
+        }
+    }
+}
+
+impl<'a> Drop for PendingGuard<'a> {
+    fn drop(&mut self) {
+        self.pending.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+struct Caller {
+    env: OwnedEnv,
+    pid: SavedTerm
+}
+
+impl Caller {
+    fn new<'a>(env: NifEnv<'a>) -> Caller {
+        let owned_env = OwnedEnv::new();
+        let pid = owned_env.save(env.pid().encode(env));
+
+        Caller {
+            env: owned_env,
+            pid: pid
+        }
+    }
+
+    fn is(&self, other: &Caller) -> bool {
+        self.env.run(| env | {
+            let pid = self.pid.load(env);
+            other.env.run(| other_env | other.pid.load(other_env).in_env(env) == pid)
+        })
+    }
+}
+
+pub struct ContextWorker {
+    commands: Mutex<Sender<Box<Command + Send>>>,
+    pending: AtomicUsize,
+    usable: AtomicBool,
+    last_caller: Arc<Mutex<Option<Caller>>>
+}
+
+impl ContextWorker {
+    pub fn spawn(context: Context) -> Result<ContextWorker, String> {
+        let (commands, receiver) = channel::<Box<Command + Send>>();
+
+        thread::Builder::new()
+            .name(String::from("exgpgme-context"))
+            .spawn(move || {
+                let mut context = context;
+                for command in receiver {
+                    command.call(&mut context);
+                }
+            })
+            .map_err(| error | error.to_string())?;
+
+        Ok(ContextWorker {
+            commands: Mutex::new(commands),
+            pending: AtomicUsize::new(0),
+            usable: AtomicBool::new(true),
+            last_caller: Arc::new(Mutex::new(None))
+        })
+    }
+
+    pub fn pending(&self) -> usize {
+        self.pending.load(Ordering::SeqCst)
+    }
+
+    pub fn is_usable(&self) -> bool {
+        self.usable.load(Ordering::SeqCst)
+    }
+
+    pub fn retire(&self) {
+        self.usable.store(false, Ordering::SeqCst);
+    }
+
+    pub fn run<F, R>(&self, operation: F) -> Result<R, String>
+        where F: FnOnce(&mut Context) -> R + Send + 'static, R: Send + 'static {
+        let (result_sender, result_receiver) = channel();
+
+        let _pending = PendingGuard::new(&self.pending);
+        let sent = match self.commands.lock() {
+            Ok(commands) => commands.send(Box::new(move | context: &mut Context | {
+                let result = panic::catch_unwind(AssertUnwindSafe(|| operation(context)));
+                let _ = result_sender.send(result.map_err(| _ | String::from("Context operation panicked")));
+            })),
+            Err(_) => return Err(String::from("Context worker is not available"))
+        };
+        if sent.is_err() {
+            return Err(String::from("Context worker is not running"));
+        }
+
+        match result_receiver.recv() {
+            Ok(result) => result,
+            Err(_) => Err(String::from("Context worker is not running"))
+        }
+    }
+
+    pub fn run_detached<F>(&self, operation: F)
+        where F: FnOnce(&mut Context) + Send + 'static {
+        if let Ok(commands) = self.commands.lock() {
+            let _ = commands.send(Box::new(move | context: &mut Context | {
+                let _ = panic::catch_unwind(AssertUnwindSafe(|| operation(context)));
+            }));
+        }
+    }
+
+    pub fn run_for_last_caller<'a, F, R>(&self, env: NifEnv<'a>, operation: F) -> Result<Option<R>, String>
+        where F: FnOnce(&mut Context) -> R + Send + 'static, R: Send + 'static {
+        let caller = Caller::new(env);
+        let last_caller = self.last_caller.clone();
+
+        self.run(move | context | {
+            let is_last_caller = match last_caller.lock() {
+                Ok(last_caller) => last_caller.as_ref().map(| last_caller | last_caller.is(&caller)).unwrap_or(false),
+                Err(_) => false
+            };
+
+            if is_last_caller {
+                Some(operation(context))
+            } else {
+                None
+            }
+        })
+    }
+
+    pub fn run_in_env<'a, F>(&self, env: NifEnv<'a>, operation: F) -> NifResult<NifTerm<'a>>
+        where F: for<'b> FnOnce(NifEnv<'b>, &mut Context) -> NifResult<NifTerm<'b>> + Send + 'static {
+        let caller = Caller::new(env);
+        let last_caller = self.last_caller.clone();
+
+        let result = self.run(move | context | {
+            if let Ok(mut last_caller) = last_caller.lock() {
+                *last_caller = Some(caller);
+            }
+
+            let owned_env = OwnedEnv::new();
+            let saved_term = owned_env.run(| worker_env | {
+                operation(worker_env, context)
+                    .map(| term | owned_env.save(term))
+            });
+            (owned_env, saved_term)
+        });
+
+        match result {
+            Ok((owned_env, Ok(saved_term))) => Ok(owned_env.run(| worker_env | saved_term.load(worker_env).in_env(env))),
+            Ok((_, Err(error))) => Err(error),
+            Err(error) => Ok((::context::helpers::atoms::error(), error).encode(env))
+        }
+    }
+}
@@ -0,0 +1,17 @@
+use gpgme;
+use gpgme::Context;
+
+pub fn with_flag<F, R>(context: &mut Context, name: &str, value: &str, operation: F) -> gpgme::Result<R>
+    where F: FnOnce(&mut Context) -> gpgme::Result<R> {
+    let previous = context.get_flag(name).map(String::from).unwrap_or_else(| _ | String::new());
+
+    context.set_flag(name, value)?;
+
+    let result = operation(context);
+    let restored = context.set_flag(name, previous.as_str());
+
+    let result = result?;
+    restored?;
+
+    Ok(result)
+}
@@ -1,6 +1,9 @@
+use gpgme;
+use gpgme::Gpgme;
 use gpgme::engine::EngineInfo;
-use rustler::{NifTerm, NifEnv, NifEncoder};
+use rustler::{NifTerm, NifEnv, NifEncoder, NifResult};
 use rustler::types::elixir_struct;
+use std::cmp::Ordering;
 use std::str::Utf8Error;
 use protocol;
 
@@ -11,9 +14,18 @@ mod atoms {
         atom home_dir;
         atom version;
         atom required_version;
+        atom ok;
+        atom error;
+        atom lt;
+        atom eq;
+        atom gt;
     }
 }
 
+lazy_static! {
+    static ref GPGME: Gpgme = gpgme::init();
+}
+
 fn get_engine_info<'a>(value: Result<&str, Option<Utf8Error>>, env: NifEnv<'a>) -> Result<NifTerm<'a>, Utf8Error> {
     match value {
         Ok(result) => Ok(String::from(result).encode(env)),
@@ -38,3 +50,93 @@ pub fn engine_info_to_term<'a>(engine_info: EngineInfo, env: NifEnv<'a>) -> Resu
             .map_put(required_version_atom, get_engine_info(engine_info.required_version(), env)?).ok().unwrap()
     )
 }
+
+pub fn engine_path(protocol: gpgme::Protocol) -> Result<String, String> {
+    let engine_infos = GPGME.engine_info().map_err(| error | error.description().into_owned())?;
+
+    for engine_info in engine_infos.iter() {
+        if engine_info.protocol() == protocol {
+            return engine_info.path()
+                .map(String::from)
+                .map_err(| _ | String::from("Could not decode engine path to utf8"));
+        }
+    }
+
+    Err(String::from("Engine is not available"))
+}
+
+pub fn library_version_string() -> &'static str {
+    GPGME.version()
+}
+
+pub fn library_version<'a>(env: NifEnv<'a>, _args: &[NifTerm<'a>]) -> NifResult<NifTerm<'a>> {
+    Ok(library_version_string().encode(env))
+}
+
+fn parse_version(version: &str) -> Option<Vec<u32>> {
+    let numbers: Vec<u32> = version
+        .split(| character: char | !character.is_digit(10) && character != '.')
+        .next()
+        .unwrap_or("")
+        .split('.')
+        .take_while(| part | !part.is_empty())
+        .map(| part | part.parse::<u32>().unwrap_or(0))
+        .collect();
+
+    if numbers.is_empty() {
+        None
+    } else {
+        Some(numbers)
+    }
+}
+
+pub fn compare_version_strings(version: &str, other_version: &str) -> Result<Ordering, String> {
+    let version = parse_version(version).ok_or_else(|| format!("Invalid version \"{}\"", version))?;
+    let other_version = parse_version(other_version).ok_or_else(|| format!("Invalid version \"{}\"", other_version))?;
+
+    for index in 0..version.len().max(other_version.len()) {
+        let part = version.get(index).cloned().unwrap_or(0);
+        let other_part = other_version.get(index).cloned().unwrap_or(0);
+        if part != other_part {
+            return Ok(part.cmp(&other_part));
+        }
+    }
+
+    Ok(Ordering::Equal)
+}
+
+pub fn version_at_least(version: &str, required_version: &str) -> bool {
+    match compare_version_strings(version, required_version) {
+        Ok(Ordering::Less) | Err(_) => false,
+        Ok(_) => true
+    }
+}
+
+pub fn compare_versions<'a>(env: NifEnv<'a>, args: &[NifTerm<'a>]) -> NifResult<NifTerm<'a>> {
+    let version: String = args[0].decode()?;
+    let other_version: String = args[1].decode()?;
+
+    match compare_version_strings(&version, &other_version) {
+        Ok(Ordering::Less) => Ok((atoms::ok(), atoms::lt()).encode(env)),
+        Ok(Ordering::Equal) => Ok((atoms::ok(), atoms::eq()).encode(env)),
+        Ok(Ordering::Greater) => Ok((atoms::ok(), atoms::gt()).encode(env)),
+        Err(error) => Ok((atoms::error(), error).encode(env))
+    }
+}
+
+pub fn engine_infos<'a>(env: NifEnv<'a>, _args: &[NifTerm<'a>]) -> NifResult<NifTerm<'a>> {
+    let engine_infos = match GPGME.engine_info() {
+        Ok(engine_infos) => engine_infos,
+        Err(error) => return Ok((atoms::error(), error.description().into_owned()).encode(env))
+    };
+
+    let mut terms: Vec<NifTerm<'a>> = Vec::new();
+    for engine_info in engine_infos.iter() {
+        match engine_info_to_term(engine_info, env) {
+            Ok(term) => terms.push(term),
+            Err(_) => return Ok((atoms::error(), String::from("Could not decode engine info to utf8")).encode(env))
+        }
+    }
+
+    Ok((atoms::ok(), terms).encode(env))
+}
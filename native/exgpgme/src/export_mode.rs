@@ -0,0 +1,41 @@
+use rustler::{NifError, NifTerm};
+use rustler::TermType;
+use rustler::types::list::NifListIterator;
+use rustler::types::tuple;
+use gpgme;
+use gpgme::ExportMode;
+
+pub fn arg_to_export_mode(terms: NifListIterator) -> Result<(ExportMode, Option<String>), NifError> {
+    let mut mode = ExportMode::empty();
+    let mut passphrase: Option<String> = None;
+
+    for term in terms {
+        match term.get_type() {
+            TermType::Atom => mode.insert(string_to_mode(term.atom_to_string()?)?),
+            TermType::Tuple if passphrase.is_none() => passphrase = Some(tuple_to_passphrase(term)?),
+            _ => return Err(NifError::BadArg)
+        }
+    }
+
+    Ok((mode, passphrase))
+}
+
+pub fn string_to_mode(name: String) -> Result<ExportMode, NifError> {
+    match name.as_ref() {
+      "extern" => Ok(gpgme::EXPORT_EXTERN),
+      "minimal" => Ok(gpgme::EXPORT_MINIMAL),
+      "secret" => Ok(gpgme::EXPORT_SECRET),
+      "raw" => Ok(gpgme::EXPORT_RAW),
+      "pkcs12" => Ok(gpgme::EXPORT_PKCS12),
+      _ => Err(NifError::BadArg)
+    }
+}
+
+fn tuple_to_passphrase(term: NifTerm) -> Result<String, NifError> {
+    let tuple = tuple::get_tuple(term)?;
+    if tuple.len() != 2 || tuple[0].atom_to_string()? != "passphrase" {
+        return Err(NifError::BadArg);
+    }
+
+    tuple[1].decode()
+}
@@ -0,0 +1,340 @@
+use std::collections::HashMap;
+use std::io::Write;
+use std::process::{Command, Stdio};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use gpgme::{Context, Protocol};
+use rustler::{NifEnv, NifTerm, NifEncoder};
+use rustler::types::elixir_struct;
+use rustler::types::atom::nil;
+use engine;
+
+mod atoms {
+    rustler_atoms! {
+        atom name;
+        atom description;
+        atom path;
+        atom options;
+        atom flags;
+        atom level;
+        atom type_ = "type";
+        atom arg_name;
+        atom default;
+        atom value;
+        atom optional_arg;
+        atom list;
+        atom runtime;
+        atom no_change;
+        atom basic;
+        atom advanced;
+        atom expert;
+        atom invisible;
+        atom internal;
+        atom none;
+        atom string;
+        atom int32;
+        atom uint32;
+        atom pathname;
+        atom ldap_server;
+        atom key_fpr;
+        atom pub_key;
+        atom sec_key;
+        atom alias_list;
+        atom other;
+    }
+}
+
+const FLAG_GROUP: u32 = 1;
+const FLAG_OPTIONAL_ARG: u32 = 2;
+const FLAG_LIST: u32 = 4;
+const FLAG_RUNTIME: u32 = 8;
+const FLAG_NO_CHANGE: u32 = 128;
+
+lazy_static! {
+    static ref OPTION_LOCKS: Mutex<HashMap<String, Arc<Mutex<()>>>> = Mutex::new(HashMap::new());
+}
+
+pub struct Component {
+    pub name: String,
+    pub description: String,
+    pub path: String
+}
+
+pub struct ComponentOption {
+    pub name: String,
+    pub flags: u32,
+    pub level: u32,
+    pub description: String,
+    pub option_type: u32,
+    pub arg_name: String,
+    pub default: Option<String>,
+    pub value: Option<String>
+}
+
+pub fn gpgconf_path() -> Result<String, String> {
+    engine::engine_path(Protocol::GpgConf).map_err(| _ | String::from("Could not find gpgconf"))
+}
+
+pub fn home_dir(context: &Context) -> Option<String> {
+    context.engine_info().home_dir().ok().map(String::from)
+}
+
+pub fn run(home_dir: Option<&str>, args: &[&str], input: Option<&str>) -> Result<String, String> {
+    let mut command = Command::new(gpgconf_path()?);
+    if let Some(home_dir) = home_dir {
+        command.arg("--homedir").arg(home_dir);
+    }
+    command.args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+
+    let mut child = command.spawn().map_err(| error | error.to_string())?;
+    let writer = match (input, child.stdin.take()) {
+        (Some(input), Some(mut stdin)) => {
+            let input = String::from(input);
+            Some(thread::spawn(move || stdin.write_all(input.as_bytes())))
+        },
+        _ => None
+    };
+
+    let output = child.wait_with_output().map_err(| error | error.to_string())?;
+    if let Some(writer) = writer {
+        match writer.join() {
+            Ok(written) => written.map_err(| error | error.to_string())?,
+            Err(_) => return Err(String::from("Could not write to gpgconf"))
+        }
+    }
+    if output.status.success() {
+        String::from_utf8(output.stdout).map_err(| _ | String::from("Could not decode gpgconf output to utf8"))
+    } else {
+        Err(String::from_utf8_lossy(&output.stderr).trim().to_string())
+    }
+}
+
+pub fn control(home_dir: Option<&str>, action: &str, component: &str) -> Result<(), String> {
+    run(home_dir, &[&format!("--{}", action), component], None)?;
+
+    Ok(())
+}
+
+pub fn launch(home_dir: Option<&str>, component: &str) -> Result<(), String> {
+    control(home_dir, "launch", component)
+}
+
+pub fn agent_socket(home_dir: Option<&str>) -> Result<String, String> {
+    let output = run(home_dir, &["--list-dirs", "agent-socket"], None)?;
+
+    Ok(unescape(output.trim()))
+}
+
+pub fn escape_string_value(value: &str) -> String {
+    let mut escaped = String::from("\"");
+    for character in value.chars() {
+        match character {
+            '%' => escaped.push_str("%25"),
+            ':' => escaped.push_str("%3a"),
+            ',' => escaped.push_str("%2c"),
+            '\n' => escaped.push_str("%0a"),
+            _ => escaped.push(character)
+        }
+    }
+    escaped
+}
+
+pub fn change_option(home_dir: Option<&str>, component: &str, option: &str, value: Option<&str>) -> Result<(), String> {
+    let line = match value {
+        Some(value) => format!("{}:0:{}\n", option, value),
+        None => format!("{}:16:\n", option)
+    };
+
+    run(home_dir, &["--runtime", "--change-options", component], Some(&line))?;
+
+    Ok(())
+}
+
+fn raw_option_value(home_dir: &str, component: &str, option: &str) -> Result<Option<String>, String> {
+    let output = run(Some(home_dir), &["--list-options", component], None)?;
+
+    Ok(output.lines()
+        .map(| line | line.split(':').collect::<Vec<&str>>())
+        .find(| fields | fields.len() >= 10 && fields[0] == option)
+        .and_then(| fields | match fields[9] {
+            "" => None,
+            value => Some(String::from(value))
+        }))
+}
+
+fn option_lock(home_dir: &str) -> Arc<Mutex<()>> {
+    let mut locks = OPTION_LOCKS.lock().unwrap_or_else(| poisoned | poisoned.into_inner());
+
+    locks.entry(String::from(home_dir)).or_insert_with(|| Arc::new(Mutex::new(()))).clone()
+}
+
+fn release_option_lock(home_dir: &str, lock: Arc<Mutex<()>>) {
+    let mut locks = OPTION_LOCKS.lock().unwrap_or_else(| poisoned | poisoned.into_inner());
+
+    if Arc::strong_count(&lock) == 2 {
+        locks.remove(home_dir);
+    }
+}
+
+pub fn with_option<F, R>(home_dir: &str, component: &str, option: &str, value: &str, operation: F) -> Result<R, String>
+    where F: FnOnce() -> R {
+    let lock = option_lock(home_dir);
+
+    let result = {
+        let _guard = lock.lock().unwrap_or_else(| poisoned | poisoned.into_inner());
+
+        raw_option_value(home_dir, component, option).and_then(| previous | {
+            change_option(Some(home_dir), component, option, Some(value))?;
+
+            let result = operation();
+            change_option(Some(home_dir), component, option, previous.as_ref().map(String::as_str))?;
+
+            Ok(result)
+        })
+    };
+
+    release_option_lock(home_dir, lock);
+
+    result
+}
+
+fn unescape(value: &str) -> String {
+    let bytes = value.as_bytes();
+    let mut unescaped: Vec<u8> = Vec::with_capacity(bytes.len());
+    let mut index = 0;
+    while index < bytes.len() {
+        if bytes[index] == b'%' && index + 2 < bytes.len() {
+            let high = (bytes[index + 1] as char).to_digit(16);
+            let low = (bytes[index + 2] as char).to_digit(16);
+            if let (Some(high), Some(low)) = (high, low) {
+                unescaped.push((high * 16 + low) as u8);
+                index += 3;
+                continue;
+            }
+        }
+        unescaped.push(bytes[index]);
+        index += 1;
+    }
+    String::from_utf8_lossy(&unescaped).into_owned()
+}
+
+fn parse_value(value: &str) -> Option<String> {
+    match value {
+        "" => None,
+        _ => Some(unescape(value.trim_left_matches('"')))
+    }
+}
+
+pub fn list_components(home_dir: Option<&str>) -> Result<Vec<Component>, String> {
+    let output = run(home_dir, &["--list-components"], None)?;
+
+    Ok(output.lines()
+        .map(| line | line.split(':').collect::<Vec<&str>>())
+        .filter(| fields | fields.len() >= 3)
+        .map(| fields | Component {
+            name: String::from(fields[0]),
+            description: unescape(fields[1]),
+            path: unescape(fields[2])
+        })
+        .collect())
+}
+
+pub fn list_options(home_dir: Option<&str>, component: &str) -> Result<Vec<ComponentOption>, String> {
+    let output = run(home_dir, &["--list-options", component], None)?;
+
+    Ok(output.lines()
+        .map(| line | line.split(':').collect::<Vec<&str>>())
+        .filter(| fields | fields.len() >= 10)
+        .map(| fields | ComponentOption {
+            name: String::from(fields[0]),
+            flags: fields[1].parse().unwrap_or(0),
+            level: fields[2].parse().unwrap_or(0),
+            description: unescape(fields[3]),
+            option_type: fields[4].parse().unwrap_or(0),
+            arg_name: unescape(fields[6]),
+            default: parse_value(fields[7]),
+            value: parse_value(fields[9])
+        })
+        .filter(| option | option.flags & FLAG_GROUP == 0)
+        .collect())
+}
+
+fn level_to_term<'a>(env: NifEnv<'a>, level: u32) -> NifTerm<'a> {
+    match level {
+        0 => atoms::basic().encode(env),
+        1 => atoms::advanced().encode(env),
+        2 => atoms::expert().encode(env),
+        3 => atoms::invisible().encode(env),
+        4 => atoms::internal().encode(env),
+        other => (atoms::other(), other).encode(env)
+    }
+}
+
+fn type_to_term<'a>(env: NifEnv<'a>, option_type: u32) -> NifTerm<'a> {
+    match option_type {
+        0 => atoms::none().encode(env),
+        1 => atoms::string().encode(env),
+        2 => atoms::int32().encode(env),
+        3 => atoms::uint32().encode(env),
+        32 => atoms::pathname().encode(env),
+        33 => atoms::ldap_server().encode(env),
+        34 => atoms::key_fpr().encode(env),
+        35 => atoms::pub_key().encode(env),
+        36 => atoms::sec_key().encode(env),
+        37 => atoms::alias_list().encode(env),
+        other => (atoms::other(), other).encode(env)
+    }
+}
+
+fn flags_to_term<'a>(env: NifEnv<'a>, flags: u32) -> NifTerm<'a> {
+    let mut terms: Vec<NifTerm<'a>> = Vec::new();
+
+    if flags & FLAG_OPTIONAL_ARG != 0 {
+        terms.push(atoms::optional_arg().encode(env));
+    }
+    if flags & FLAG_LIST != 0 {
+        terms.push(atoms::list().encode(env));
+    }
+    if flags & FLAG_RUNTIME != 0 {
+        terms.push(atoms::runtime().encode(env));
+    }
+    if flags & FLAG_NO_CHANGE != 0 {
+        terms.push(atoms::no_change().encode(env));
+    }
+
+    terms.encode(env)
+}
+
+fn optional_string_to_term<'a>(env: NifEnv<'a>, value: &Option<String>) -> NifTerm<'a> {
+    match *value {
+        Some(ref value) => value.encode(env),
+        None => nil().encode(env)
+    }
+}
+
+pub fn component_option_to_term<'a>(env: NifEnv<'a>, option: &ComponentOption) -> NifTerm<'a> {
+    elixir_struct::make_ex_struct(env, "Elixir.ExGpgme.GpgConf.ComponentOption").ok().unwrap()
+        .map_put(atoms::name().encode(env), option.name.encode(env)).ok().unwrap()
+        .map_put(atoms::description().encode(env), option.description.encode(env)).ok().unwrap()
+        .map_put(atoms::flags().encode(env), flags_to_term(env, option.flags)).ok().unwrap()
+        .map_put(atoms::level().encode(env), level_to_term(env, option.level)).ok().unwrap()
+        .map_put(atoms::type_().encode(env), type_to_term(env, option.option_type)).ok().unwrap()
+        .map_put(atoms::arg_name().encode(env), option.arg_name.encode(env)).ok().unwrap()
+        .map_put(atoms::default().encode(env), optional_string_to_term(env, &option.default)).ok().unwrap()
+        .map_put(atoms::value().encode(env), optional_string_to_term(env, &option.value)).ok().unwrap()
+}
+
+pub fn component_to_term<'a>(env: NifEnv<'a>, component: &Component, options: &[ComponentOption]) -> NifTerm<'a> {
+    let options: Vec<NifTerm<'a>> = options.iter()
+        .map(| option | component_option_to_term(env, option))
+        .collect();
+
+    elixir_struct::make_ex_struct(env, "Elixir.ExGpgme.GpgConf.Component").ok().unwrap()
+        .map_put(atoms::name().encode(env), component.name.encode(env)).ok().unwrap()
+        .map_put(atoms::description().encode(env), component.description.encode(env)).ok().unwrap()
+        .map_put(atoms::path().encode(env), component.path.encode(env)).ok().unwrap()
+        .map_put(atoms::options().encode(env), options.encode(env)).ok().unwrap()
+}
@@ -5,3 +5,10 @@ macro_rules! string_or_null {
         Err(Some(error)) => Err(error)
     });
 }
+
+macro_rules! nif_or_nil {
+    ($expr:expr, $env:ident, $content:ident, $content_to_env:expr) => (match $expr {
+        Some($content) => $content_to_env.encode($env),
+        None => $crate::rustler::types::atom::nil().encode($env)
+    });
+}
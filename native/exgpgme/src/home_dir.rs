@@ -0,0 +1,61 @@
+use std::env;
+use std::fs;
+use std::io;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::os::unix::fs::DirBuilderExt;
+use gpgconf;
+
+const CREATE_ATTEMPTS: usize = 16;
+
+pub struct TemporaryHomeDir {
+    path: PathBuf
+}
+
+impl TemporaryHomeDir {
+    pub fn create() -> io::Result<TemporaryHomeDir> {
+        for _ in 0..CREATE_ATTEMPTS {
+            let path = env::temp_dir().join(format!("exgpgme-{}", random_suffix()?));
+
+            match fs::DirBuilder::new().mode(0o700).create(&path) {
+                Ok(()) => return Ok(TemporaryHomeDir {
+                    path: path
+                }),
+                Err(ref error) if error.kind() == io::ErrorKind::AlreadyExists => continue,
+                Err(error) => return Err(error)
+            }
+        }
+
+        Err(io::Error::new(io::ErrorKind::AlreadyExists, "Could not find an unused temporary home dir name"))
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn write_file(&self, name: &str, contents: &str) -> io::Result<()> {
+        let mut file = fs::File::create(self.path.join(name))?;
+        file.write_all(contents.as_bytes())
+    }
+}
+
+impl Drop for TemporaryHomeDir {
+    fn drop(&mut self) {
+        let path = self.path.clone();
+
+        thread::spawn(move || {
+            if let Some(home_dir) = path.to_str() {
+                let _ = gpgconf::control(Some(home_dir), "kill", "all");
+            }
+            let _ = fs::remove_dir_all(&path);
+        });
+    }
+}
+
+fn random_suffix() -> io::Result<String> {
+    let mut bytes = [0u8; 12];
+    fs::File::open("/dev/urandom")?.read_exact(&mut bytes)?;
+
+    Ok(bytes.iter().map(| byte | format!("{:02x}", byte)).collect())
+}
@@ -0,0 +1,111 @@
+use rustler::{NifError, NifTerm};
+use rustler::TermType;
+use rustler::types::list::NifListIterator;
+use rustler::types::tuple;
+use gpgme;
+use gpgme::Context;
+use passphrase;
+use context_flag::with_flag;
+
+pub struct ImportOptions {
+    options: Vec<&'static str>,
+    filter: Option<String>,
+    passphrase: Option<String>,
+}
+
+pub fn arg_to_import_options(terms: NifListIterator) -> Result<ImportOptions, NifError> {
+    let mut import_options = ImportOptions {
+        options: Vec::new(),
+        filter: None,
+        passphrase: None
+    };
+
+    for term in terms {
+        match term.get_type() {
+            TermType::Atom => import_options.options.push(string_to_option(term.atom_to_string()?)?),
+            TermType::Tuple => match tuple_to_passphrase(term)? {
+                Some(passphrase) => {
+                    if import_options.passphrase.is_some() {
+                        return Err(NifError::BadArg);
+                    }
+                    import_options.passphrase = Some(passphrase);
+                },
+                None => {
+                    if import_options.filter.is_some() {
+                        return Err(NifError::BadArg);
+                    }
+                    import_options.filter = Some(tuple_to_filter(term)?);
+                }
+            },
+            _ => return Err(NifError::BadArg)
+        }
+    }
+
+    Ok(import_options)
+}
+
+pub fn string_to_option(name: String) -> Result<&'static str, NifError> {
+    match name.as_ref() {
+        "import_local_sigs" => Ok("import-local-sigs"),
+        "keep_ownertrust" => Ok("keep-ownertrust"),
+        "repair_pks_subkey_bug" => Ok("repair-pks-subkey-bug"),
+        "import_minimal" => Ok("import-minimal"),
+        "import_clean" => Ok("import-clean"),
+        "self_sigs_only" => Ok("self-sigs-only"),
+        "repair_keys" => Ok("repair-keys"),
+        "import_drop_uids" => Ok("import-drop-uids"),
+        "restore" => Ok("import-restore"),
+        "merge_only" => Ok("merge-only"),
+        _ => Err(NifError::BadArg)
+    }
+}
+
+fn tuple_to_passphrase(term: NifTerm) -> Result<Option<String>, NifError> {
+    let tuple = tuple::get_tuple(term)?;
+    if tuple.len() != 2 || tuple[0].atom_to_string()? != "passphrase" {
+        return Ok(None);
+    }
+
+    Ok(Some(tuple[1].decode()?))
+}
+
+fn tuple_to_filter(term: NifTerm) -> Result<String, NifError> {
+    let tuple = tuple::get_tuple(term)?;
+    if tuple.len() != 2 {
+        return Err(NifError::BadArg);
+    }
+
+    let name = match tuple[0].atom_to_string()?.as_ref() {
+        "keep_uid" => "keep-uid",
+        "drop_subkey" => "drop-subkey",
+        "drop_sig" => "drop-sig",
+        _ => return Err(NifError::BadArg)
+    };
+    let expression: String = tuple[1].decode()?;
+
+    Ok(format!("{}={}", name, expression))
+}
+
+fn with_optional_flag<F, R>(context: &mut Context, name: &str, value: Option<&str>, operation: F) -> gpgme::Result<R>
+    where F: FnOnce(&mut Context) -> gpgme::Result<R> {
+    match value {
+        Some(value) => with_flag(context, name, value, operation),
+        None => operation(context)
+    }
+}
+
+pub fn with_import_options<F, R>(context: &mut Context, import_options: &ImportOptions, operation: F) -> gpgme::Result<R>
+    where F: FnOnce(&mut Context) -> gpgme::Result<R> {
+    let options = import_options.options.join(",");
+    let options = if options.is_empty() { None } else { Some(options.as_str()) };
+    let filter = import_options.filter.as_ref().map(String::as_str);
+
+    with_optional_flag(context, "import-options", options, | context | {
+        with_optional_flag(context, "import-filter", filter, | context | {
+            match import_options.passphrase {
+                Some(ref passphrase) => passphrase::with_passphrase(context, passphrase, operation),
+                None => operation(context)
+            }
+        })
+    })
+}
@@ -0,0 +1,58 @@
+use rustler::{NifEnv, NifTerm, NifEncoder, NifError};
+use rustler::types::list::NifListIterator;
+use gpgme;
+use gpgme::KeyListMode;
+
+mod atoms {
+    rustler_atoms! {
+        atom local;
+        atom extern_ = "extern";
+        atom sigs;
+        atom sig_notations;
+        atom with_secret;
+        atom with_tofu;
+        atom ephemeral;
+        atom validate;
+    }
+}
+
+pub fn arg_to_key_list_mode(atoms: NifListIterator) -> Result<KeyListMode, NifError> {
+    let mut mode = KeyListMode::empty();
+
+    for atom in atoms {
+        let name = atom.atom_to_string()?;
+
+        mode.insert(string_to_mode(name)?);
+    }
+
+    Ok(mode)
+}
+
+pub fn string_to_mode(name: String) -> Result<KeyListMode, NifError> {
+    match name.as_ref() {
+      "local" => Ok(gpgme::KEY_LIST_MODE_LOCAL),
+      "extern" => Ok(gpgme::KEY_LIST_MODE_EXTERN),
+      "sigs" => Ok(gpgme::KEY_LIST_MODE_SIGS),
+      "sig_notations" => Ok(gpgme::KEY_LIST_MODE_SIG_NOTATIONS),
+      "with_secret" => Ok(gpgme::KEY_LIST_MODE_WITH_SECRET),
+      "with_tofu" => Ok(gpgme::KEY_LIST_MODE_WITH_TOFU),
+      "ephemeral" => Ok(gpgme::KEY_LIST_MODE_EPHEMERAL),
+      "validate" => Ok(gpgme::KEY_LIST_MODE_VALIDATE),
+      _ => Err(NifError::BadArg)
+    }
+}
+
+pub fn key_list_mode_to_term<'a>(mode: KeyListMode, env: NifEnv<'a>) -> NifTerm<'a> {
+    let mut modes: Vec<NifTerm<'a>> = Vec::new();
+
+    if mode.contains(gpgme::KEY_LIST_MODE_LOCAL) { modes.push(atoms::local().encode(env)); }
+    if mode.contains(gpgme::KEY_LIST_MODE_EXTERN) { modes.push(atoms::extern_().encode(env)); }
+    if mode.contains(gpgme::KEY_LIST_MODE_SIGS) { modes.push(atoms::sigs().encode(env)); }
+    if mode.contains(gpgme::KEY_LIST_MODE_SIG_NOTATIONS) { modes.push(atoms::sig_notations().encode(env)); }
+    if mode.contains(gpgme::KEY_LIST_MODE_WITH_SECRET) { modes.push(atoms::with_secret().encode(env)); }
+    if mode.contains(gpgme::KEY_LIST_MODE_WITH_TOFU) { modes.push(atoms::with_tofu().encode(env)); }
+    if mode.contains(gpgme::KEY_LIST_MODE_EPHEMERAL) { modes.push(atoms::ephemeral().encode(env)); }
+    if mode.contains(gpgme::KEY_LIST_MODE_VALIDATE) { modes.push(atoms::validate().encode(env)); }
+
+    modes.encode(env)
+}
@@ -2,6 +2,10 @@ use rustler::resource::ResourceArc;
 use rustler::NifError;
 use gpgme::keys::Key;
 
+pub mod key_details;
+pub mod subkey;
+pub mod user_id;
+
 pub struct KeyResource {
     pub key: Key
 }
@@ -28,9 +32,9 @@ macro_rules! unpack_key_list {
             .map(| key_arg | { key_arg.decode::<ResourceArc<keys::KeyResource>>() })
             .collect::<NifResult<Vec<ResourceArc<keys::KeyResource>>>>()?;
 
-        let $keys: Vec<&Key> = keys_with_errors
+        let $keys: Vec<Key> = keys_with_errors
             .iter()
-            .map(| ref key_arc | &key_arc.key)
+            .map(| ref key_arc | key_arc.key.clone())
             .collect();
     );
 }
@@ -0,0 +1,76 @@
+use rustler::{NifEnv, NifTerm, NifEncoder};
+use gpgme::keys::Subkey;
+use std::time::UNIX_EPOCH;
+use rustler::types::elixir_struct;
+use std::str::Utf8Error;
+use key_algorithm::transform_key_algorithm;
+
+mod atoms {
+    rustler_atoms! {
+        atom id;
+        atom fingerprint;
+        atom keygrip;
+        atom key_algorithm;
+        atom length;
+        atom curve;
+        atom creation_time;
+        atom expiration_time;
+        atom is_revoked;
+        atom is_expired;
+        atom is_disabled;
+        atom is_invalid;
+        atom is_secret;
+        atom can_encrypt;
+        atom can_sign;
+        atom can_certify;
+        atom can_authenticate;
+    }
+}
+
+pub fn transform_subkey<'a>(env: NifEnv<'a>, subkey: Subkey) -> Result<NifTerm<'a>, Utf8Error> {
+    let id_atom = atoms::id().encode(env);
+    let fingerprint_atom = atoms::fingerprint().encode(env);
+    let keygrip_atom = atoms::keygrip().encode(env);
+    let key_algorithm_atom = atoms::key_algorithm().encode(env);
+    let length_atom = atoms::length().encode(env);
+    let curve_atom = atoms::curve().encode(env);
+    let creation_time_atom = atoms::creation_time().encode(env);
+    let expiration_time_atom = atoms::expiration_time().encode(env);
+    let is_revoked_atom = atoms::is_revoked().encode(env);
+    let is_expired_atom = atoms::is_expired().encode(env);
+    let is_disabled_atom = atoms::is_disabled().encode(env);
+    let is_invalid_atom = atoms::is_invalid().encode(env);
+    let is_secret_atom = atoms::is_secret().encode(env);
+    let can_encrypt_atom = atoms::can_encrypt().encode(env);
+    let can_sign_atom = atoms::can_sign().encode(env);
+    let can_certify_atom = atoms::can_certify().encode(env);
+    let can_authenticate_atom = atoms::can_authenticate().encode(env);
+
+    let id = string_or_null!(subkey.id(), env)?;
+    let fingerprint = string_or_null!(subkey.fingerprint(), env)?;
+    let keygrip = string_or_null!(subkey.keygrip(), env)?;
+    let curve = string_or_null!(subkey.curve(), env)?;
+    let creation_time = nif_or_nil!(subkey.creation_time(), env, content, { content.duration_since(UNIX_EPOCH).expect("time").as_secs() });
+    let expiration_time = nif_or_nil!(subkey.expiration_time(), env, content, { content.duration_since(UNIX_EPOCH).expect("time").as_secs() });
+
+    Ok(
+        elixir_struct::make_ex_struct(env, "Elixir.ExGpgme.Keys.Subkey").ok().unwrap()
+            .map_put(id_atom, id).ok().unwrap()
+            .map_put(fingerprint_atom, fingerprint).ok().unwrap()
+            .map_put(keygrip_atom, keygrip).ok().unwrap()
+            .map_put(key_algorithm_atom, transform_key_algorithm(env, subkey.algorithm())).ok().unwrap()
+            .map_put(length_atom, subkey.length().encode(env)).ok().unwrap()
+            .map_put(curve_atom, curve).ok().unwrap()
+            .map_put(creation_time_atom, creation_time).ok().unwrap()
+            .map_put(expiration_time_atom, expiration_time).ok().unwrap()
+            .map_put(is_revoked_atom, subkey.is_revoked().encode(env)).ok().unwrap()
+            .map_put(is_expired_atom, subkey.is_expired().encode(env)).ok().unwrap()
+            .map_put(is_disabled_atom, subkey.is_disabled().encode(env)).ok().unwrap()
+            .map_put(is_invalid_atom, subkey.is_invalid().encode(env)).ok().unwrap()
+            .map_put(is_secret_atom, subkey.is_secret().encode(env)).ok().unwrap()
+            .map_put(can_encrypt_atom, subkey.can_encrypt().encode(env)).ok().unwrap()
+            .map_put(can_sign_atom, subkey.can_sign().encode(env)).ok().unwrap()
+            .map_put(can_certify_atom, subkey.can_certify().encode(env)).ok().unwrap()
+            .map_put(can_authenticate_atom, subkey.can_authenticate().encode(env)).ok().unwrap()
+    )
+}
@@ -0,0 +1,43 @@
+use rustler::{NifEnv, NifTerm, NifEncoder};
+use gpgme::keys::UserId;
+use rustler::types::elixir_struct;
+use std::str::Utf8Error;
+use validity::transform_validity;
+
+mod atoms {
+    rustler_atoms! {
+        atom id;
+        atom name;
+        atom email;
+        atom comment;
+        atom validity;
+        atom is_revoked;
+        atom is_invalid;
+    }
+}
+
+pub fn transform_user_id<'a>(env: NifEnv<'a>, user_id: UserId) -> Result<NifTerm<'a>, Utf8Error> {
+    let id_atom = atoms::id().encode(env);
+    let name_atom = atoms::name().encode(env);
+    let email_atom = atoms::email().encode(env);
+    let comment_atom = atoms::comment().encode(env);
+    let validity_atom = atoms::validity().encode(env);
+    let is_revoked_atom = atoms::is_revoked().encode(env);
+    let is_invalid_atom = atoms::is_invalid().encode(env);
+
+    let id = string_or_null!(user_id.id(), env)?;
+    let name = string_or_null!(user_id.name(), env)?;
+    let email = string_or_null!(user_id.email(), env)?;
+    let comment = string_or_null!(user_id.comment(), env)?;
+
+    Ok(
+        elixir_struct::make_ex_struct(env, "Elixir.ExGpgme.Keys.UserId").ok().unwrap()
+            .map_put(id_atom, id).ok().unwrap()
+            .map_put(name_atom, name).ok().unwrap()
+            .map_put(email_atom, email).ok().unwrap()
+            .map_put(comment_atom, comment).ok().unwrap()
+            .map_put(validity_atom, transform_validity(env, user_id.validity())).ok().unwrap()
+            .map_put(is_revoked_atom, user_id.is_revoked().encode(env)).ok().unwrap()
+            .map_put(is_invalid_atom, user_id.is_invalid().encode(env)).ok().unwrap()
+    )
+}
@@ -10,19 +10,31 @@ use rustler::schedule::NifScheduleFlags;
 mod context;
 mod results;
 mod engine;
+mod capabilities;
 mod protocol;
 mod encrypt_flags;
+mod import_options;
+mod export_mode;
 mod pinentry_mode;
 mod sign_mode;
+mod audit_log_format;
+mod key_list_mode;
 mod validity;
 mod key_algorithm;
 mod hash_algorithm;
 mod notation;
+mod home_dir;
+mod passphrase;
+mod context_flag;
+mod gpgconf;
 
 rustler_export_nifs! {
     "Elixir.ExGpgme.Context",
     [
-        ("from_protocol", 1, context::from_protocol),
+        ("from_protocol", 2, context::from_protocol),
+        ("ephemeral", 2, context::ephemeral),
+        ("settings", 1, context::settings),
+        ("clone", 1, context::clone),
         ("protocol", 1, context::protocol),
         ("armor?", 1, context::armor),
         ("set_armor", 2, context::set_armor),
@@ -30,22 +42,63 @@ rustler_export_nifs! {
         ("set_text_mode", 2, context::set_text_mode),
         ("offline?", 1, context::offline),
         ("set_offline", 2, context::set_offline),
+        ("include_certs", 1, context::include_certs),
+        ("set_include_certs", 2, context::set_include_certs),
         ("get_flag", 2, context::get_flag),
         ("set_flag", 3, context::set_flag),
         ("engine_info", 1, context::engine_info),
+        ("library_version", 0, engine::library_version),
+        ("compare_versions", 2, engine::compare_versions),
+        ("capabilities", 1, context::capabilities),
+        ("engine_infos", 0, engine::engine_infos, NifScheduleFlags::DirtyIo),
         ("set_engine_path", 2, context::set_engine_path),
         ("set_engine_home_dir", 2, context::set_engine_home_dir),
+        ("key_list_mode", 1, context::key_list_mode),
+        ("set_key_list_mode", 2, context::set_key_list_mode),
+        ("signers", 1, context::signers),
+        ("add_signer", 2, context::add_signer),
+        ("clear_signers", 1, context::clear_signers),
+        ("set_kill_on_drop", 2, context::set_kill_on_drop),
+        ("keyserver", 1, context::keyserver),
+        ("set_keyserver", 2, context::set_keyserver),
+        ("sender", 1, context::sender),
+        ("set_sender", 2, context::set_sender),
         ("pinentry_mode", 1, context::pinentry_mode),
         ("set_pinentry_mode", 2, context::set_pinentry_mode),
-        ("import", 2, context::import, NifScheduleFlags::DirtyIo),
+        ("add_signature_notation", 4, context::add_signature_notation),
+        ("clear_signature_notations", 1, context::clear_signature_notations),
+        ("signature_notations", 1, context::signature_notations),
+        ("set_signature_policy_url", 3, context::set_signature_policy_url),
+        ("signature_policy_url", 1, context::signature_policy_url),
+        ("import_with_options", 3, context::import_with_options, NifScheduleFlags::DirtyIo),
+        ("import_keys", 2, context::import_keys, NifScheduleFlags::DirtyIo),
+        ("receive_keys", 2, context::receive_keys, NifScheduleFlags::DirtyIo),
+        ("send_keys", 2, context::send_keys, NifScheduleFlags::DirtyIo),
+        ("export_keys", 3, context::export_keys, NifScheduleFlags::DirtyIo),
+        ("locate_key", 2, context::locate_key, NifScheduleFlags::DirtyIo),
+        ("inspect_keys", 2, context::inspect_keys, NifScheduleFlags::DirtyIo),
+        ("key_details", 1, context::key_details),
         ("find_key", 2, context::find_key, NifScheduleFlags::DirtyIo),
         ("delete_key", 2, context::delete_key, NifScheduleFlags::DirtyIo),
         ("delete_secret_key", 2, context::delete_secret_key, NifScheduleFlags::DirtyIo),
         ("decrypt", 2, context::decrypt, NifScheduleFlags::DirtyIo),
         ("encrypt_with_flags", 4, context::encrypt_with_flags, NifScheduleFlags::DirtyIo),
+        ("encrypt_symmetric_with_flags", 5, context::encrypt_symmetric_with_flags, NifScheduleFlags::DirtyIo),
         ("sign_and_encrypt_with_flags", 4, context::sign_and_encrypt_with_flags, NifScheduleFlags::DirtyIo),
+        ("decrypt_with_passphrase", 3, context::decrypt_with_passphrase, NifScheduleFlags::DirtyIo),
+        ("decrypt_export_session_key", 2, context::decrypt_export_session_key, NifScheduleFlags::DirtyIo),
+        ("decrypt_with_session_key", 3, context::decrypt_with_session_key, NifScheduleFlags::DirtyIo),
         ("sign_with_mode", 3, context::sign_with_mode, NifScheduleFlags::DirtyIo),
         ("verify_opaque", 3, context::verify_opaque, NifScheduleFlags::DirtyIo),
+        ("generate_certificate_request", 3, context::generate_certificate_request, NifScheduleFlags::DirtyIo),
+        ("audit_log", 2, context::audit_log, NifScheduleFlags::DirtyIo),
+        ("verify_opaque_with_audit_log", 4, context::verify_opaque_with_audit_log, NifScheduleFlags::DirtyIo),
+        ("decrypt_with_audit_log", 3, context::decrypt_with_audit_log, NifScheduleFlags::DirtyIo),
+        ("assuan_transact_with_inquiries", 3, context::assuan_transact_with_inquiries, NifScheduleFlags::DirtyIo),
+        ("agent_context", 1, context::agent_context, NifScheduleFlags::DirtyIo),
+        ("gpgconf_control", 3, context::gpgconf_control, NifScheduleFlags::DirtyIo),
+        ("gpgconf_components", 1, context::gpgconf_components, NifScheduleFlags::DirtyIo),
+        ("gpgconf_change_option", 4, context::gpgconf_change_option, NifScheduleFlags::DirtyIo),
     ],
     Some(on_load)
 }
@@ -1 +1,2 @@
 pub mod signature_notation;
+pub mod signature_notation_flags;
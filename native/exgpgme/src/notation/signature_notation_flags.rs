@@ -0,0 +1,24 @@
+use rustler::{NifError};
+use rustler::types::list::NifListIterator;
+use gpgme;
+use gpgme::SignatureNotationFlags;
+
+pub fn arg_to_signature_notation_flags(atoms: NifListIterator) -> Result<SignatureNotationFlags, NifError> {
+    let mut flags = SignatureNotationFlags::empty();
+
+    for atom in atoms {
+        let name = atom.atom_to_string()?;
+
+        flags.insert(string_to_flag(name)?);
+    }
+
+    Ok(flags)
+}
+
+pub fn string_to_flag(name: String) -> Result<SignatureNotationFlags, NifError> {
+    match name.as_ref() {
+      "human_readable" => Ok(gpgme::NOTATION_HUMAN_READABLE),
+      "critical" => Ok(gpgme::NOTATION_CRITICAL),
+      _ => Err(NifError::BadArg)
+    }
+}
@@ -0,0 +1,24 @@
+use std::io::Write;
+use gpgme;
+use gpgme::{Context, PinentryMode, PassphraseRequest};
+use context_flag::with_flag;
+
+pub fn with_passphrase<F, R>(context: &mut Context, passphrase: &str, operation: F) -> gpgme::Result<R>
+    where F: FnOnce(&mut Context) -> gpgme::Result<R> {
+    let pinentry_mode = context.pinentry_mode();
+    context.set_pinentry_mode(PinentryMode::Loopback)?;
+
+    let result = context.with_passphrase_provider(| _: PassphraseRequest, out: &mut Write | {
+        out.write_all(passphrase.as_bytes())?;
+        Ok(())
+    }, operation);
+
+    context.set_pinentry_mode(pinentry_mode)?;
+
+    result
+}
+
+pub fn without_symmetric_key_cache<F, R>(context: &mut Context, operation: F) -> gpgme::Result<R>
+    where F: FnOnce(&mut Context) -> gpgme::Result<R> {
+    with_flag(context, "no-symkey-cache", "1", operation)
+}
@@ -0,0 +1,36 @@
+use rustler::{NifEnv, NifTerm, NifEncoder};
+use rustler::types::elixir_struct;
+use rustler::types::binary::OwnedNifBinary;
+use rustler::types::atom::nil;
+
+mod atoms {
+    rustler_atoms! {
+        atom data;
+        atom status;
+        atom inquiries;
+    }
+}
+
+fn line_to_term<'a>(env: NifEnv<'a>, line: &(String, Option<String>)) -> NifTerm<'a> {
+    match line.1 {
+        Some(ref args) => (line.0.as_str(), args.as_str()).encode(env),
+        None => (line.0.as_str(), nil()).encode(env)
+    }
+}
+
+pub fn transform_assuan_result<'a>(env: NifEnv<'a>, data: &[u8], status: &[(String, Option<String>)], inquiries: &[(String, Option<String>)]) -> NifTerm<'a> {
+    let data_atom = atoms::data().encode(env);
+    let status_atom = atoms::status().encode(env);
+    let inquiries_atom = atoms::inquiries().encode(env);
+
+    let mut binary = OwnedNifBinary::new(data.len()).unwrap();
+    binary.as_mut_slice().copy_from_slice(data);
+
+    let status: Vec<NifTerm<'a>> = status.iter().map(| line | line_to_term(env, line)).collect();
+    let inquiries: Vec<NifTerm<'a>> = inquiries.iter().map(| line | line_to_term(env, line)).collect();
+
+    elixir_struct::make_ex_struct(env, "Elixir.ExGpgme.Results.AssuanResult").ok().unwrap()
+        .map_put(data_atom, binary.release(env).encode(env)).ok().unwrap()
+        .map_put(status_atom, status.encode(env)).ok().unwrap()
+        .map_put(inquiries_atom, inquiries.encode(env)).ok().unwrap()
+}
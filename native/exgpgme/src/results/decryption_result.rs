@@ -0,0 +1,32 @@
+use rustler::{NifEnv, NifTerm, NifEncoder};
+use gpgme::results::DecryptionResult;
+use rustler::types::elixir_struct;
+use std::str::Utf8Error;
+
+mod atoms {
+    rustler_atoms! {
+        atom filename;
+        atom unsupported_algorithm;
+        atom is_wrong_key_usage;
+        atom session_key;
+    }
+}
+
+pub fn transform_decryption_result<'a>(env: NifEnv<'a>, decryption_result: DecryptionResult) -> Result<NifTerm<'a>, Utf8Error> {
+    let filename_atom = atoms::filename().encode(env);
+    let unsupported_algorithm_atom = atoms::unsupported_algorithm().encode(env);
+    let is_wrong_key_usage_atom = atoms::is_wrong_key_usage().encode(env);
+    let session_key_atom = atoms::session_key().encode(env);
+
+    let filename = string_or_null!(decryption_result.filename(), env)?;
+    let unsupported_algorithm = string_or_null!(decryption_result.unsupported_algorithm(), env)?;
+    let session_key = string_or_null!(decryption_result.session_key(), env)?;
+
+    Ok(
+        elixir_struct::make_ex_struct(env, "Elixir.ExGpgme.Results.DecryptionResult").ok().unwrap()
+            .map_put(filename_atom, filename).ok().unwrap()
+            .map_put(unsupported_algorithm_atom, unsupported_algorithm).ok().unwrap()
+            .map_put(is_wrong_key_usage_atom, decryption_result.is_wrong_key_usage().encode(env)).ok().unwrap()
+            .map_put(session_key_atom, session_key).ok().unwrap()
+    )
+}
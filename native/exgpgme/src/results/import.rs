@@ -1,18 +1,51 @@
 use rustler::{NifEnv, NifTerm, NifEncoder};
+use gpgme;
 use gpgme::results::Import;
 use rustler::types::elixir_struct;
+use rustler::types::atom::nil;
 
 mod atoms {
     rustler_atoms! {
         atom fingerprint;
+        atom status;
+        atom error;
+        atom new;
+        atom new_user_id;
+        atom new_signature;
+        atom new_subkey;
+        atom secret;
     }
 }
 
 pub fn transform_import<'a>(env: NifEnv<'a>, import: Import) -> NifTerm<'a> {
     let fingerprint_atom = atoms::fingerprint().encode(env);
+    let status_atom = atoms::status().encode(env);
+    let error_atom = atoms::error().encode(env);
 
-    let fingerprint = import.fingerprint().expect("must be a string");
+    let fingerprint = match import.fingerprint() {
+        Ok(fingerprint) => String::from(fingerprint).encode(env),
+        Err(_) => nil().encode(env)
+    };
+    let error = match import.result() {
+        Ok(_) => nil().encode(env),
+        Err(error) => error.description().into_owned().encode(env)
+    };
 
     elixir_struct::make_ex_struct(env, "Elixir.ExGpgme.Results.Import").ok().unwrap()
-        .map_put(fingerprint_atom, String::from(fingerprint).encode(env)).ok().unwrap()
+        .map_put(fingerprint_atom, fingerprint).ok().unwrap()
+        .map_put(status_atom, transform_import_status(env, &import)).ok().unwrap()
+        .map_put(error_atom, error).ok().unwrap()
+}
+
+fn transform_import_status<'a>(env: NifEnv<'a>, import: &Import) -> NifTerm<'a> {
+    let status = import.status();
+    let mut flags: Vec<NifTerm<'a>> = Vec::new();
+
+    if status.contains(gpgme::IMPORT_NEW) { flags.push(atoms::new().encode(env)); }
+    if status.contains(gpgme::IMPORT_UID) { flags.push(atoms::new_user_id().encode(env)); }
+    if status.contains(gpgme::IMPORT_SIG) { flags.push(atoms::new_signature().encode(env)); }
+    if status.contains(gpgme::IMPORT_SUBKEY) { flags.push(atoms::new_subkey().encode(env)); }
+    if status.contains(gpgme::IMPORT_SECRET) { flags.push(atoms::secret().encode(env)); }
+
+    flags.encode(env)
 }
@@ -3,3 +3,5 @@ pub mod signature;
 pub mod verification_result;
 pub mod import;
 pub mod import_result;
+pub mod decryption_result;
+pub mod assuan_result;
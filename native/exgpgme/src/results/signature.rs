@@ -1,5 +1,6 @@
 use rustler::{NifEnv, NifTerm, NifEncoder};
 use gpgme::results::Signature;
+use gpgme::keys::Key;
 use std::time::UNIX_EPOCH;
 use rustler::types::elixir_struct;
 use rustler::types::atom::nil;
@@ -31,17 +32,13 @@ mod atoms {
         atom policy_url;
         atom notations;
         atom key;
+        atom issuer_serial;
+        atom issuer_name;
+        atom chain_id;
     }
 }
 
-macro_rules! nif_or_nil {
-    ($expr:expr, $env:ident, $content:ident, $content_to_env:expr) => (match $expr {
-        Some($content) => $content_to_env.encode($env),
-        None => nil().encode($env)
-    });
-}
-
-pub fn transform_signature<'a>(env: NifEnv<'a>, signature: Signature) -> Result<NifTerm<'a>, Utf8Error> {
+pub fn transform_signature<'a>(env: NifEnv<'a>, signature: Signature, signer: Option<Key>) -> Result<NifTerm<'a>, Utf8Error> {
     let fingerprint_atom = atoms::fingerprint().encode(env);
     let status_atom = atoms::status().encode(env);
     let creation_time_atom = atoms::creation_time().encode(env);
@@ -58,6 +55,9 @@ pub fn transform_signature<'a>(env: NifEnv<'a>, signature: Signature) -> Result<
     let policy_url_atom = atoms::policy_url().encode(env);
     let notations_atom = atoms::notations().encode(env);
     let key_atom = atoms::key().encode(env);
+    let issuer_serial_atom = atoms::issuer_serial().encode(env);
+    let issuer_name_atom = atoms::issuer_name().encode(env);
+    let chain_id_atom = atoms::chain_id().encode(env);
 
     let status = match signature.status() {
         Ok(_) => atoms::valid().encode(env),
@@ -72,7 +72,15 @@ pub fn transform_signature<'a>(env: NifEnv<'a>, signature: Signature) -> Result<
         None => nil().encode(env)
     };
     let policy_url = string_or_null!(signature.policy_url(), env)?;
-    let key_arc = match signature.key() {
+    let (issuer_serial, issuer_name, chain_id) = match signer {
+        Some(ref key) => (
+            string_or_null!(key.issuer_serial(), env)?,
+            string_or_null!(key.issuer_name(), env)?,
+            string_or_null!(key.chain_id(), env)?
+        ),
+        None => (nil().encode(env), nil().encode(env), nil().encode(env))
+    };
+    let key_arc = match signer {
         Some(key) => wrap_key(key).encode(env),
         None => nil().encode(env)
     };
@@ -112,5 +120,8 @@ pub fn transform_signature<'a>(env: NifEnv<'a>, signature: Signature) -> Result<
             .map_put(policy_url_atom, policy_url).ok().unwrap()
             .map_put(notations_atom, notations).ok().unwrap()
             .map_put(key_atom, key_arc).ok().unwrap()
+            .map_put(issuer_serial_atom, issuer_serial).ok().unwrap()
+            .map_put(issuer_name_atom, issuer_name).ok().unwrap()
+            .map_put(chain_id_atom, chain_id).ok().unwrap()
     )
 }
@@ -1,9 +1,12 @@
 use rustler::{NifEnv, NifTerm, NifEncoder};
-use gpgme::results::VerificationResult;
+use gpgme::{Context, Protocol};
+use gpgme::keys::Key;
+use gpgme::results::{Signature, VerificationResult};
 use rustler::types::elixir_struct;
 use std::str::Utf8Error;
 
 use ::results::signature::transform_signature;
+use ::context::settings::engine_context;
 
 mod atoms {
     rustler_atoms! {
@@ -12,15 +15,36 @@ mod atoms {
     }
 }
 
-pub fn transform_verification_result<'a>(env: NifEnv<'a>, verification_result: VerificationResult) -> Result<NifTerm<'a>, Utf8Error> {
+fn find_signer(context: &Context, lookup_context: &mut Option<Context>, signature: &Signature) -> Option<Key> {
+    if let Some(key) = signature.key() {
+        return Some(key);
+    }
+    if context.protocol() != Protocol::Cms {
+        return None;
+    }
+
+    let fingerprint = match signature.fingerprint() {
+        Ok(fingerprint) => fingerprint,
+        Err(_) => return None
+    };
+    if lookup_context.is_none() {
+        *lookup_context = engine_context(context).ok();
+    }
+
+    lookup_context.as_mut().and_then(| lookup_context | lookup_context.find_key(fingerprint).ok())
+}
+
+pub fn transform_verification_result<'a>(env: NifEnv<'a>, verification_result: VerificationResult, context: &Context) -> Result<NifTerm<'a>, Utf8Error> {
     let filename_atom = atoms::filename().encode(env);
     let signatures_atom = atoms::signatures().encode(env);
 
     let filename = string_or_null!(verification_result.filename(), env)?;
 
+    let mut lookup_context: Option<Context> = None;
     let signatures: NifTerm = verification_result.signatures()
         .map(| signature | {
-            transform_signature(env, signature)
+            let signer = find_signer(context, &mut lookup_context, &signature);
+            transform_signature(env, signature, signer)
         })
         .fold(Ok(Vec::new()), | acc, signature | {
             match acc {
@@ -0,0 +1,74 @@
+defmodule ExGpgme.AgentTest do
+  @moduledoc false
+
+  use ExUnit.Case
+  alias ExGpgme.{Agent, Context, GpgConf}
+  alias ExGpgme.Agent.KeyInfo
+
+  doctest Agent, except: [
+    connect: 1,
+    preset_passphrase: 4,
+    clear_passphrase: 2,
+    key_infos: 1,
+  ]
+
+  @sender_secret_key File.read!("priv/test/keys/sender_secret.asc")
+  @sender_keygrip "10183231CECBA577374EDCEA5F97A0469CEB8AF0"
+
+  setup do
+    dirname = :erlang.crc32("#{inspect make_ref()}")
+    path = "priv/test/keychains/#{dirname}"
+
+    File.mkdir!(path)
+    File.chmod!(path, 0o700)
+
+    on_exit fn ->
+      System.cmd("gpgconf", ["--homedir", path, "--kill", "gpg-agent"])
+      File.rm_rf!(path)
+    end
+
+    context = Context.from_protocol!(:open_pgp, engine_home_dir: path, pinentry_mode: :loopback)
+    Context.import!(context, @sender_secret_key)
+    GpgConf.change_option!(context, "gpg-agent", "allow-preset-passphrase", true)
+
+    {:ok, %{context: context}}
+  end
+
+  defp sender_key_info(context) do
+    Enum.find(Agent.key_infos!(context), &(&1.keygrip == @sender_keygrip))
+  end
+
+  describe "connect/1" do
+    test "reuses the agent context", %{context: context} do
+      assert {:ok, agent} = Agent.connect(context)
+      assert {:ok, ^agent} = Agent.connect(agent)
+
+      assert :ok = Agent.preset_passphrase(agent, @sender_keygrip, "secret")
+      assert {:ok, key_infos} = Agent.key_infos(agent)
+      assert %KeyInfo{cached: true} = Enum.find(key_infos, &(&1.keygrip == @sender_keygrip))
+    end
+  end
+
+  describe "key_infos/1" do
+    test "lists keys of the agent", %{context: context} do
+      assert {:ok, key_infos} = Agent.key_infos(context)
+      assert %KeyInfo{type: :disk, cached: false} = Enum.find(key_infos, &(&1.keygrip == @sender_keygrip))
+    end
+  end
+
+  describe "preset_passphrase/4" do
+    test "caches passphrase until cleared", %{context: context} do
+      assert :ok = Agent.preset_passphrase(context, @sender_keygrip, "secret")
+      assert %KeyInfo{cached: true} = sender_key_info(context)
+
+      assert :ok = Agent.clear_passphrase(context, @sender_keygrip)
+      assert %KeyInfo{cached: false} = sender_key_info(context)
+    end
+
+    test "errors without allow-preset-passphrase", %{context: context} do
+      GpgConf.change_option!(context, "gpg-agent", "allow-preset-passphrase", nil)
+
+      assert {:error, _error} = Agent.preset_passphrase(context, @sender_keygrip, "secret")
+    end
+  end
+end
@@ -2,22 +2,49 @@ defmodule ExGpgme.ContextTest do
   @moduledoc false
 
   use ExUnit.Case
-  alias ExGpgme.Context
-  alias ExGpgme.Results.{VerificationResult, Signature, ImportResult}
+  alias ExGpgme.{Context, GpgConf, Wkd}
+  alias ExGpgme.GpgConf.ComponentOption
+  alias ExGpgme.Results.{AssuanResult, VerificationResult, Signature, ImportResult, Import, DecryptionResult}
+  alias ExGpgme.Keys.{KeyDetails, UserId, Subkey}
+  alias ExGpgme.Test.{HkpStub, WkdStub}
+  alias ExGpgme.Notation.SignatureNotation
 
   doctest Context, except: [
-    from_protocol: 1,
-    from_protocol!: 1,
-    import: 2,
+    from_protocol: 2,
+    from_protocol!: 2,
+    signature_notations: 1,
+    settings: 1,
+    ephemeral: 2,
+    assuan_transact: 3,
+    capabilities: 1,
+    supports?: 2,
+    clone: 1,
+    add_signer: 2,
+    import: 3,
+    import_keys: 2,
+    receive_keys: 2,
+    send_keys: 2,
+    export_keys: 3,
+    locate_key: 2,
+    inspect_keys: 2,
+    key_details: 1,
     find_key: 2,
     encrypt: 4,
+    encrypt_symmetric: 5,
     sign_and_encrypt: 4,
     engine_info: 1,
     delete_key: 2,
     delete_secret_key: 2,
     decrypt: 2,
+    decrypt_with_passphrase: 3,
+    decrypt_export_session_key: 2,
+    decrypt_with_session_key: 3,
     sign: 3,
     verify_opaque: 3,
+    create_certificate_request: 2,
+    verify_opaque_with_audit_log: 4,
+    decrypt_with_audit_log: 3,
+    audit_log: 2,
   ]
 
   @sender_fingerprint "95E93F470BCB2E96C648572DFBFA85913EE05E95"
@@ -32,6 +59,13 @@ defmodule ExGpgme.ContextTest do
 
   @encrypted_receiver File.read!("priv/test/test_data/encrypted_receiver.asc")
 
+  @x509_fingerprint "75EF3F0E1D279B4F7E8F2641277C844E65483AA6"
+  @x509_keygrip "5A2678AC1C05651E519290F264027B1EF8F384CE"
+  @x509_issuer_serial "6736F31C61069293"
+  @x509_issuer_name "CN=ExGpgme Test,O=ExGpgme"
+  @x509_pkcs12 File.read!("priv/test/keys/sender_x509.p12")
+  @x509_pkcs12_passphrase "test pass"
+
   setup_all do
     @keychain_base_dir
     |> File.ls!
@@ -50,6 +84,58 @@ defmodule ExGpgme.ContextTest do
     1 = imported + unchanged
   end
 
+  def cms_context!(options \\ []) do
+    context = Context.ephemeral!(:cms, [armor: true] ++ options)
+    home_dir = Context.settings!(context).engine_home_dir
+    private_keys_dir = Path.join(home_dir, "private-keys-v1.d")
+
+    File.write!(Path.join(home_dir, "gpgsm.conf"), "disable-crl-checks\n")
+    File.write!(Path.join(home_dir, "trustlist.txt"), "#{@x509_fingerprint} S relax\n")
+    File.mkdir_p!(private_keys_dir)
+    File.chmod!(private_keys_dir, 0o700)
+    File.cp!("priv/test/keys/sender_x509.key", Path.join(private_keys_dir, "#{@x509_keygrip}.key"))
+
+    import_test_key!(context, File.read!("priv/test/keys/sender_x509.pem"))
+    Context.add_signer!(context, Context.find_key!(context, @x509_fingerprint))
+
+    context
+  end
+
+  defp signers_uid(clear_signed) do
+    [_, armored] =
+      Regex.run(~r/-----BEGIN PGP SIGNATURE-----\r?\n(?:[^\n]+: [^\n]*\n)*\r?\n(.+?)\r?\n=/s, clear_signed)
+
+    armored
+    |> String.replace(~r/\s/, "")
+    |> Base.decode64!()
+    |> signature_packet_body()
+    |> hashed_subpackets()
+    |> Map.get(28)
+  end
+
+  defp signature_packet_body(<<0b10::2, 2::4, 0::2, length::8, body::binary-size(length), _::binary>>), do: body
+  defp signature_packet_body(<<0b10::2, 2::4, 1::2, length::16, body::binary-size(length), _::binary>>), do: body
+  defp signature_packet_body(<<0b11::2, 2::6, length::8, body::binary-size(length), _::binary>>) when length < 192,
+    do: body
+  defp signature_packet_body(<<0b11::2, 2::6, first::8, second::8, rest::binary>>) when first in 192..223 do
+    length = (first - 192) * 256 + second + 192
+    <<body::binary-size(length), _::binary>> = rest
+    body
+  end
+
+  defp hashed_subpackets(<<4, _type, _key_algorithm, _hash_algorithm, length::16, subpackets::binary-size(length),
+      _::binary>>),
+    do: subpackets(subpackets, %{})
+
+  defp subpackets(<<>>, acc), do: acc
+  defp subpackets(<<length, rest::binary>>, acc) when length < 192, do: subpacket(length, rest, acc)
+  defp subpackets(<<first, second, rest::binary>>, acc) when first in 192..254,
+    do: subpacket((first - 192) * 256 + second + 192, rest, acc)
+  defp subpackets(<<255, length::32, rest::binary>>, acc), do: subpacket(length, rest, acc)
+
+  defp subpacket(length, <<type, data::binary-size(length - 1), rest::binary>>, acc),
+    do: subpackets(rest, Map.put(acc, Bitwise.band(type, 0x7f), data))
+
   setup(tags) do
     context = if tags[:context] do
       dirname = :erlang.crc32("#{inspect make_ref()}")
@@ -62,7 +148,7 @@ defmodule ExGpgme.ContextTest do
         File.rm_rf!(path)
       end
 
-      context = Context.from_protocol!(:open_pgp)
+      context = Context.from_protocol!(:open_pgp, pool_size: tags[:pool_size] || 1)
       Context.set_pinentry_mode!(context, :loopback)
       Context.set_engine_home_dir!(context, path)
 
@@ -132,6 +218,240 @@ defmodule ExGpgme.ContextTest do
     end
   end
 
+  describe "from_protocol/2" do
+    test "applies options" do
+      assert {:ok, context} = Context.from_protocol(:open_pgp, armor: true, text_mode: true, offline: true,
+        pinentry_mode: :loopback, flags: [{"raw-description", "1"}], sender: "alice@example.com",
+        keyserver: "hkp://127.0.0.1:11371", engine_home_dir: @keychain_base_dir, include_certs: 1)
+
+      assert Context.armor?(context)
+      assert Context.text_mode?(context)
+      assert Context.offline?(context)
+      assert 1 = Context.include_certs(context)
+      assert :loopback = Context.pinentry_mode(context)
+      assert {:ok, "1"} = Context.get_flag(context, "raw-description")
+      assert "alice@example.com" = Context.sender(context)
+      assert "hkp://127.0.0.1:11371" = Context.keyserver(context)
+    end
+
+    test "errors on unknown options" do
+      assert {:error, "Unknown option foo"} = Context.from_protocol(:open_pgp, foo: true)
+    end
+
+    test "errors on keyserver without explicit home dir" do
+      assert {:error, message} = Context.from_protocol(:open_pgp, keyserver: "hkp://127.0.0.1:11371")
+      assert message =~ "set_engine_home_dir/2"
+    end
+
+    test "errors on invalid options" do
+      assert {:error, "Invalid value for option armor"} = Context.from_protocol(:open_pgp, armor: "yes")
+      assert {:error, "Invalid value for option pinentry_mode"} = Context.from_protocol(:open_pgp, pinentry_mode: :foo)
+      assert {:error, "Invalid value for option flags: " <> _} =
+        Context.from_protocol(:open_pgp, flags: [{"not-existing-flag", "1"}])
+      assert {:error, "Options must be a keyword list"} = Context.from_protocol(:open_pgp, [:armor])
+    end
+  end
+
+  describe "from_protocol/2 with pool" do
+    @tag context: true, import_receiver_secret: true, armor: true, pool_size: 4
+    test "runs operations concurrently on all contexts", %{context: context} do
+      assert %{pool_size: 4, armor: true} = Context.settings!(context)
+
+      recipient = Context.find_key!(context, @receiver_fingerprint)
+
+      results = 1..16
+      |> Task.async_stream(fn index ->
+        cyphertext = Context.encrypt!(context, [recipient], "Hello #{index}!", [:always_trust])
+        {index, cyphertext, Context.decrypt(context, cyphertext)}
+      end)
+      |> Enum.map(fn {:ok, result} -> result end)
+
+      for {index, cyphertext, decrypted} <- results do
+        assert cyphertext =~ "-BEGIN PGP MESSAGE-"
+        assert {:ok, "Hello #{index}!"} == decrypted
+      end
+    end
+
+    test "errors with invalid pool size" do
+      assert {:error, "Invalid value for option pool_size"} = Context.from_protocol(:open_pgp, pool_size: 0)
+    end
+
+    test "runs operations while another context of the pool is busy" do
+      context = Context.ephemeral!(:open_pgp, pool_size: 2)
+      import_test_key!(context, @sender_public_key)
+      {:ok, listen_socket} = :gen_tcp.listen(0, [:binary, active: false, ip: {127, 0, 0, 1}])
+      {:ok, port} = :inet.port(listen_socket)
+      Context.set_keyserver(context, "hkp://127.0.0.1:#{port}")
+
+      busy = Task.async(fn -> Context.receive_keys(context, [@receiver_fingerprint]) end)
+      {:ok, socket} = :gen_tcp.accept(listen_socket, 10_000)
+
+      lookup = Task.async(fn -> Context.find_key(context, @sender_fingerprint) end)
+      assert {:ok, _key} = Task.await(lookup, 5_000)
+
+      :gen_tcp.close(socket)
+      :gen_tcp.close(listen_socket)
+      Task.await(busy, 30_000)
+    end
+  end
+
+  describe "capabilities/1" do
+    @tag context: true
+    test "reports every capability", %{context: context} do
+      capabilities = Context.capabilities(context)
+
+      for capability <- [:pinentry_loopback, :sender, :session_key_export, :override_session_key, :quick_key_creation,
+                         :tofu, :locate_key, :import_filter, :no_symkey_cache] do
+        assert is_boolean(capabilities[capability])
+      end
+      assert 9 = map_size(capabilities)
+
+      assert capabilities.pinentry_loopback
+    end
+
+    test "uses the engine of the context protocol" do
+      context = Context.from_protocol!(:cms)
+
+      assert Context.supports?(context, :pinentry_loopback)
+      assert Context.supports?(context, :sender)
+    end
+  end
+
+  describe "assuan_transact/3" do
+    setup %{context: context} do
+      home_dir = context
+      |> Context.engine_info!
+      |> Map.fetch!(:home_dir)
+      |> Path.expand
+
+      {_, 0} = System.cmd("gpgconf", ["--homedir", home_dir, "--launch", "gpg-agent"])
+      {socket, 0} = System.cmd("gpgconf", ["--homedir", home_dir, "--list-dirs", "agent-socket"])
+
+      on_exit fn ->
+        System.cmd("gpgconf", ["--homedir", home_dir, "--kill", "gpg-agent"])
+      end
+
+      {:ok, %{assuan: Context.from_protocol!(:assuan, engine_path: String.trim(socket))}}
+    end
+
+    @tag context: true
+    test "returns data lines", %{assuan: assuan} do
+      assert {:ok, %AssuanResult{data: version, inquiries: []}} = Context.assuan_transact(assuan, "GETINFO version")
+      assert version =~ ~r/^\d+\.\d+/
+    end
+
+    @tag context: true
+    test "answers inquiries", %{assuan: assuan} do
+      assert {:ok, %AssuanResult{data: data, inquiries: [{"KEYPARAM", _}]}} =
+        Context.assuan_transact(assuan, "GENKEY --no-protection", %{
+          "KEYPARAM" => "(genkey(ecc(curve 7:Ed25519)(flags eddsa)))"
+        })
+
+      assert data =~ "public-key"
+    end
+
+    @tag context: true
+    test "cancels unanswered inquiries", %{assuan: assuan} do
+      assert {:error, _error} = Context.assuan_transact(assuan, "GENKEY --no-protection")
+    end
+
+    @tag context: true
+    test "errors with unknown command", %{assuan: assuan} do
+      assert {:error, _error} = Context.assuan_transact(assuan, "FOO")
+    end
+  end
+
+  describe "ephemeral/2" do
+    test "creates context with empty private home dir" do
+      assert {:ok, context} = Context.ephemeral(:open_pgp, armor: true)
+
+      assert %{armor: true, pinentry_mode: :loopback, engine_home_dir: home_dir} = Context.settings!(context)
+      assert %File.Stat{type: :directory, mode: mode} = File.stat!(home_dir)
+      assert Bitwise.band(mode, 0o777) == 0o700
+      assert File.read!(Path.join(home_dir, "gpg-agent.conf")) =~ "allow-loopback-pinentry"
+
+      assert {:error, "End of file"} = Context.find_key(context, @sender_fingerprint)
+    end
+
+    test "keeps key rings apart" do
+      first = Context.ephemeral!(:open_pgp)
+      second = Context.ephemeral!(:open_pgp)
+
+      import_test_key!(first, @sender_secret_key)
+
+      assert {:ok, _key} = Context.find_key(first, @sender_fingerprint)
+      assert {:error, "End of file"} = Context.find_key(second, @sender_fingerprint)
+    end
+
+    test "deletes the home dir when garbage collected" do
+      home_dir = fn ->
+        context = Context.ephemeral!(:open_pgp)
+        import_test_key!(context, @sender_secret_key)
+        Context.settings!(context).engine_home_dir
+      end
+      |> Task.async
+      |> Task.await
+
+      assert Enum.any?(1..50, fn _ ->
+        :erlang.garbage_collect()
+        Process.sleep(100)
+        not File.exists?(home_dir)
+      end)
+    end
+  end
+
+  describe "settings/1" do
+    @tag context: true, import_sender_secret: true
+    test "gives settings", %{context: context} do
+      signer = Context.find_key!(context, @sender_fingerprint)
+      Context.add_signer!(context, signer)
+      Context.set_armor(context, true)
+      Context.set_flag(context, "raw-description", "1")
+
+      assert {:ok, settings} = Context.settings(context)
+      assert %{protocol: :open_pgp, armor: true, pinentry_mode: :loopback, key_list_mode: [:local],
+        flags: [{"raw-description", "1"}], signers: [_signer], sender: nil, keyserver: nil} = settings
+      assert settings.engine_home_dir =~ "priv/test/keychains/"
+    end
+  end
+
+  describe "clone/1" do
+    @tag context: true, import_sender_secret: true
+    test "creates independent context with same settings", %{context: context} do
+      Context.add_signer!(context, Context.find_key!(context, @sender_fingerprint))
+      Context.set_armor(context, true)
+      Context.set_sender!(context, "alice@example.com")
+      Context.set_keyserver(context, "hkp://127.0.0.1:11371")
+
+      assert {:ok, clone} = Context.clone(context)
+      assert Context.settings!(clone) == Context.settings!(context)
+
+      Context.set_armor(clone, false)
+      assert Context.armor?(context)
+    end
+  end
+
+  describe "set_key_list_mode/2" do
+    @tag context: true
+    test "set correctly", %{context: context} do
+      assert :ok = Context.set_key_list_mode(context, [:local, :sigs])
+      assert [:local, :sigs] = Context.key_list_mode(context)
+    end
+  end
+
+  describe "add_signer/2" do
+    @tag context: true, import_sender_secret: true
+    test "adds and clears signers", %{context: context} do
+      key = Context.find_key!(context, @sender_fingerprint)
+
+      assert :ok = Context.add_signer(context, key)
+      assert [_key] = Context.signers(context)
+
+      assert :ok = Context.clear_signers(context)
+      assert [] = Context.signers(context)
+    end
+  end
+
   describe "protocol/1" do
     @tag context: true
     test "gives protocol", %{context: context} do
@@ -147,6 +467,68 @@ defmodule ExGpgme.ContextTest do
 
       assert 1 = Enum.count(imports)
     end
+
+    @tag context: true
+    test "reports per key status", %{context: context} do
+      assert {:ok, %ImportResult{imports: [import]}} = Context.import(context, @sender_public_key)
+      assert %Import{fingerprint: @sender_fingerprint, status: [:new], error: nil} = import
+
+      assert {:ok, %ImportResult{imports: [import]}} = Context.import(context, @sender_public_key)
+      assert %Import{fingerprint: @sender_fingerprint, status: [], error: nil} = import
+    end
+  end
+
+  describe "import/3" do
+    @tag context: true
+    test "only merges existing keys with :merge_only", %{context: context} do
+      assert {:ok, %ImportResult{imported: 0}} = Context.import(context, @sender_public_key, [:merge_only])
+      assert {:error, "End of file"} = Context.find_key(context, @sender_fingerprint)
+    end
+
+    @tag context: true
+    test "imports with options and filter", %{context: context} do
+      assert {:ok, %ImportResult{imported: 1}} =
+        Context.import(context, @sender_public_key, [:import_clean, {:keep_uid, "uid =~ @"}])
+    end
+
+    @tag context: true
+    test "keeps previously set import flags", %{context: context} do
+      :ok = Context.set_flag(context, "import-options", "import-minimal")
+      :ok = Context.set_flag(context, "import-filter", "keep-uid=uid =~ Sender")
+
+      assert {:ok, %ImportResult{imported: 1}} =
+        Context.import(context, @sender_public_key, [:import_clean, {:keep_uid, "uid =~ @"}])
+      assert {:ok, "import-minimal"} = Context.get_flag(context, "import-options")
+      assert {:ok, "keep-uid=uid =~ Sender"} = Context.get_flag(context, "import-filter")
+    end
+
+    @tag context: true
+    test "gives argument error on wrong options", %{context: context} do
+      assert_raise ArgumentError, fn ->
+        Context.import(context, @sender_public_key, [:foo])
+      end
+      assert_raise ArgumentError, fn ->
+        Context.import(context, @sender_public_key, [{:keep_uid, "uid =~ @"}, {:drop_sig, "sig_created < 1"}])
+      end
+      assert_raise ArgumentError, fn ->
+        Context.import(context, @sender_public_key, [passphrase: "first", passphrase: "second"])
+      end
+    end
+
+    test "imports PKCS#12 bundle with passphrase" do
+      context = Context.ephemeral!(:cms)
+
+      assert {:ok, %ImportResult{secret_imported: 1}} =
+        Context.import(context, @x509_pkcs12, passphrase: @x509_pkcs12_passphrase)
+      assert {:ok, _key} = Context.find_key(context, @x509_fingerprint)
+    end
+
+    test "does not import PKCS#12 bundle with wrong passphrase" do
+      context = Context.ephemeral!(:cms)
+
+      assert {:error, _} = Context.import(context, @x509_pkcs12, passphrase: "wrong passphrase")
+      assert {:error, _} = Context.find_key(context, @x509_fingerprint)
+    end
   end
 
   describe "import!/2" do
@@ -159,6 +541,207 @@ defmodule ExGpgme.ContextTest do
     end
   end
 
+  describe "import_keys/2" do
+    @tag context: true, import_sender_public: true
+    test "imports keys from key handles", %{context: context} do
+      key = Context.find_key!(context, @sender_fingerprint)
+      assert {:ok, %ImportResult{considered: 1}} = Context.import_keys(context, [key])
+    end
+
+    @tag context: true
+    test "gives argument error without keys", %{context: context} do
+      assert_raise ArgumentError, fn ->
+        Context.import_keys(context, [])
+      end
+    end
+  end
+
+  describe "set_keyserver/2" do
+    @tag context: true
+    test "set correctly", %{context: context} do
+      assert nil == Context.keyserver(context)
+      assert :ok = Context.set_keyserver(context, "hkp://127.0.0.1:11371")
+      assert "hkp://127.0.0.1:11371" = Context.keyserver(context)
+    end
+
+    test "refuses a keyserver without explicit home dir" do
+      context = Context.from_protocol!(:open_pgp)
+
+      assert {:error, message} = Context.set_keyserver(context, "hkp://127.0.0.1:11371")
+      assert message =~ "set_engine_home_dir/2"
+      assert nil == Context.keyserver(context)
+    end
+  end
+
+  describe "receive_keys/2" do
+    test "receives keys from the keyserver" do
+      context = Context.ephemeral!(:open_pgp)
+      {:ok, keyserver} = HkpStub.start([@sender_public_key])
+      Context.set_keyserver(context, HkpStub.url(keyserver))
+
+      assert {:ok, %ImportResult{imported: 1}} = Context.receive_keys(context, [@sender_fingerprint])
+      assert {:ok, _key} = Context.find_key(context, @sender_fingerprint)
+    end
+
+    @tag context: true
+    test "restores the keyserver of the home dir", %{context: context} do
+      :ok = GpgConf.change_option(context, "dirmngr", "keyserver", "hkp://127.0.0.1:11371")
+      {:ok, keyserver} = HkpStub.start([@sender_public_key])
+      Context.set_keyserver!(context, HkpStub.url(keyserver))
+
+      assert {:ok, %ImportResult{imported: 1}} = Context.receive_keys(context, [@sender_fingerprint])
+      assert {:ok, %ComponentOption{value: "hkp://127.0.0.1:11371"}} = GpgConf.option(context, "dirmngr", "keyserver")
+    end
+  end
+
+  describe "send_keys/2" do
+    test "sends keys to the keyserver" do
+      context = Context.ephemeral!(:open_pgp)
+      import_test_key!(context, @sender_public_key)
+      {:ok, keyserver} = HkpStub.start()
+      Context.set_keyserver(context, HkpStub.url(keyserver))
+
+      key = Context.find_key!(context, @sender_fingerprint)
+      assert :ok = Context.send_keys(context, [key])
+      assert [upload] = HkpStub.uploads(keyserver)
+      assert upload =~ "-BEGIN PGP PUBLIC KEY BLOCK-"
+    end
+
+    test "keeps the keyserver of each context sharing a home dir" do
+      context = Context.ephemeral!(:open_pgp)
+      import_test_key!(context, @sender_public_key)
+      {:ok, keyserver} = HkpStub.start()
+      Context.set_keyserver(context, HkpStub.url(keyserver))
+
+      clone = Context.clone!(context)
+      {:ok, clone_keyserver} = HkpStub.start()
+      Context.set_keyserver(clone, HkpStub.url(clone_keyserver))
+
+      key = Context.find_key!(context, @sender_fingerprint)
+      assert :ok = Context.send_keys(clone, [key])
+      assert :ok = Context.send_keys(context, [key])
+      assert [_upload] = HkpStub.uploads(keyserver)
+      assert [_upload] = HkpStub.uploads(clone_keyserver)
+    end
+
+    @tag context: true, import_sender_public: true
+    test "sends keys with the keyserver of a context with explicit home dir", %{context: context} do
+      {:ok, keyserver} = HkpStub.start()
+      Context.set_keyserver!(context, HkpStub.url(keyserver))
+
+      key = Context.find_key!(context, @sender_fingerprint)
+      assert :ok = Context.send_keys(context, [key])
+      assert [_upload] = HkpStub.uploads(keyserver)
+      assert {:ok, %ComponentOption{value: nil}} = GpgConf.option(context, "dirmngr", "keyserver")
+    end
+  end
+
+  describe "export_keys/3" do
+    @tag context: true, import_sender_public: true, armor: true
+    test "exports keys", %{context: context} do
+      key = Context.find_key!(context, @sender_fingerprint)
+      assert {:ok, exported} = Context.export_keys(context, [key], [:minimal])
+      assert exported =~ "-BEGIN PGP PUBLIC KEY BLOCK-"
+    end
+
+    @tag context: true, import_sender_public: true
+    test "exports binary keys without armor", %{context: context} do
+      key = Context.find_key!(context, @sender_fingerprint)
+      assert {:ok, exported} = Context.export_keys(context, [key], [])
+      assert {:ok, [%KeyDetails{fingerprint: @sender_fingerprint}]} = Context.inspect_keys(context, exported)
+    end
+
+    test "exports PKCS#12 bundle with passphrase" do
+      context = cms_context!()
+      key = Context.find_key!(context, @x509_fingerprint)
+
+      assert {:ok, bundle} = Context.export_keys(context, [key], [:secret, :pkcs12, passphrase: "export passphrase"])
+
+      other = Context.ephemeral!(:cms)
+      assert {:ok, %ImportResult{secret_imported: 1}} =
+        Context.import(other, bundle, passphrase: "export passphrase")
+    end
+  end
+
+  describe "locate_key/2" do
+    test "looks up the Web Key Directory of the domain" do
+      context = Context.ephemeral!(:open_pgp)
+      {:ok, proxy} = WkdStub.start()
+      File.write!(Path.join(Context.settings!(context).engine_home_dir, "dirmngr.conf"), WkdStub.dirmngr_conf(proxy))
+
+      assert {:ok, []} = Context.locate_key(context, "sender@example.com")
+      assert Enum.any?(WkdStub.hosts(proxy), &(&1 in ["openpgpkey.example.com:443", "example.com:443"]))
+    end
+
+    @tag :wkd_ca
+    test "gives the key of the Web Key Directory" do
+      publisher = Context.ephemeral!(:open_pgp)
+      import_test_key!(publisher, @sender_public_key)
+      directory = Path.join(Context.settings!(publisher).engine_home_dir, "wkd")
+      Wkd.generate!(publisher, [Context.find_key!(publisher, @sender_fingerprint)], "example.com", directory)
+
+      context = Context.ephemeral!(:open_pgp)
+      {:ok, proxy} = WkdStub.start(directory)
+      File.write!(Path.join(Context.settings!(context).engine_home_dir, "dirmngr.conf"), WkdStub.dirmngr_conf(proxy))
+
+      assert {:ok, [%KeyDetails{fingerprint: @sender_fingerprint}]} =
+        Context.locate_key(context, "sender@example.com")
+      assert "/.well-known/openpgpkey/hu/#{Wkd.hash_local_part("sender")}" in WkdStub.paths(proxy)
+      assert {:ok, _key} = Context.find_key(context, @sender_fingerprint)
+    end
+
+    test "restores the auto-key-locate flag" do
+      context = Context.ephemeral!(:open_pgp, flags: [{"auto-key-locate", "local"}])
+      {:ok, proxy} = WkdStub.start()
+      File.write!(Path.join(Context.settings!(context).engine_home_dir, "dirmngr.conf"), WkdStub.dirmngr_conf(proxy))
+
+      assert {:ok, []} = Context.locate_key(context, "sender@example.com")
+      assert {:ok, "local"} = Context.get_flag(context, "auto-key-locate")
+      assert [_ | _] = WkdStub.hosts(proxy)
+    end
+  end
+
+  describe "inspect_keys/2" do
+    @tag context: true
+    test "gives key details without importing", %{context: context} do
+      assert {:ok, [%KeyDetails{fingerprint: @sender_fingerprint} = key_details]} =
+        Context.inspect_keys(context, @sender_public_key)
+
+      assert %KeyDetails{user_ids: [%UserId{} | _], subkeys: [%Subkey{fingerprint: @sender_fingerprint} | _]} =
+        key_details
+      assert {:error, "End of file"} = Context.find_key(context, @sender_fingerprint)
+    end
+
+    @tag context: true
+    test "gives details of all keys in the data", %{context: context} do
+      assert {:ok, [%KeyDetails{fingerprint: @sender_fingerprint}, %KeyDetails{fingerprint: @receiver_fingerprint}]} =
+        Context.inspect_keys(context, @sender_public_key <> @receiver_public_key)
+      assert {:error, "End of file"} = Context.find_key(context, @receiver_fingerprint)
+    end
+  end
+
+  describe "key_details/1" do
+    @tag context: true, import_sender_public: true
+    test "gives key details", %{context: context} do
+      key = Context.find_key!(context, @sender_fingerprint)
+      assert {:ok, %KeyDetails{fingerprint: @sender_fingerprint, has_secret: false}} = Context.key_details(key)
+    end
+
+    @tag context: true, import_sender_public: true
+    test "leaves X.509 fields empty for OpenPGP keys", %{context: context} do
+      key = Context.find_key!(context, @sender_fingerprint)
+      assert {:ok, %KeyDetails{issuer_serial: nil, issuer_name: nil, chain_id: nil}} = Context.key_details(key)
+    end
+
+    test "gives X.509 fields for CMS certificates" do
+      context = cms_context!()
+      key = Context.find_key!(context, @x509_fingerprint)
+
+      assert {:ok, %KeyDetails{protocol: :cms, issuer_serial: @x509_issuer_serial, issuer_name: @x509_issuer_name,
+        chain_id: @x509_fingerprint}} = Context.key_details(key)
+    end
+  end
+
   describe "find_key/2" do
     @tag context: true, import_sender_public: true
     test "finds key", %{context: context} do
@@ -199,6 +782,27 @@ defmodule ExGpgme.ContextTest do
       assert {:ok, "Hello World!"} = Context.decrypt(context, cyphertext)
     end
 
+    @tag context: true, import_receiver_secret: true
+    test "encrypts binary data without armor", %{context: context} do
+      recipient = Context.find_key!(context, @receiver_fingerprint)
+      data = <<0, 255, 128, 1>>
+
+      assert {:ok, cyphertext} = Context.encrypt(context, [recipient], data, [:always_trust])
+      refute String.valid?(cyphertext)
+
+      assert {:ok, ^data} = Context.decrypt(context, cyphertext)
+    end
+
+    test "encrypts with CMS" do
+      context = cms_context!()
+      recipient = Context.find_key!(context, @x509_fingerprint)
+
+      assert {:ok, cyphertext} = Context.encrypt(context, [recipient], "Hello World!")
+      assert cyphertext =~ "-BEGIN ENCRYPTED MESSAGE-"
+
+      assert {:ok, "Hello World!"} = Context.decrypt(context, cyphertext)
+    end
+
     @tag context: true
     test "errors with missing key", %{context: context} do
       assert_raise ArgumentError, fn ->
@@ -207,6 +811,36 @@ defmodule ExGpgme.ContextTest do
     end
   end
 
+  describe "encrypt_symmetric/5" do
+    @tag context: true, armor: true
+    test "encrypts without recipients", %{context: context} do
+      assert {:ok, cyphertext} = Context.encrypt_symmetric(context, "passphrase", "Hello World!")
+      assert cyphertext =~ "-BEGIN PGP MESSAGE-"
+
+      assert {:ok, "Hello World!"} = Context.decrypt_with_passphrase(context, "passphrase", cyphertext)
+    end
+
+    @tag context: true, import_receiver_secret: true, armor: true
+    test "encrypts for passphrase and recipients", %{context: context} do
+      recipient = Context.find_key!(context, @receiver_fingerprint)
+
+      assert {:ok, cyphertext} =
+        Context.encrypt_symmetric(context, "passphrase", "Hello World!", [recipient], [:always_trust])
+
+      assert {:ok, "Hello World!"} = Context.decrypt_with_passphrase(context, "passphrase", cyphertext)
+      assert {:ok, "Hello World!"} = Context.decrypt(context, cyphertext)
+    end
+  end
+
+  describe "decrypt_with_passphrase/3" do
+    @tag context: true, armor: true
+    test "errors with wrong passphrase", %{context: context} do
+      cyphertext = Context.encrypt_symmetric!(context, "passphrase", "Hello World!")
+
+      assert {:error, _} = Context.decrypt_with_passphrase(context, "wrong passphrase", cyphertext)
+    end
+  end
+
   describe "decrypt/2" do
     @tag context: true, import_receiver_secret: true, armor: true
     test "decrypts correctly", %{context: context} do
@@ -214,6 +848,54 @@ defmodule ExGpgme.ContextTest do
     end
   end
 
+  describe "decrypt_export_session_key/2" do
+    @tag context: true, import_receiver_secret: true
+    test "exports the session key", %{context: context} do
+      assert {:ok, {"Hello World!", %DecryptionResult{session_key: session_key}}} =
+        Context.decrypt_export_session_key(context, @encrypted_receiver)
+
+      assert is_binary(session_key)
+      assert {:ok, ""} = Context.get_flag(context, "export-session-key")
+    end
+
+    @tag context: true, import_receiver_secret: true
+    test "keeps a previously set export-session-key flag", %{context: context} do
+      :ok = Context.set_flag(context, "export-session-key", "1")
+
+      assert {:ok, {"Hello World!", _}} = Context.decrypt_export_session_key(context, @encrypted_receiver)
+      assert {:ok, "1"} = Context.get_flag(context, "export-session-key")
+    end
+  end
+
+  describe "decrypt_with_session_key/3" do
+    @tag context: true, import_receiver_secret: true
+    test "decrypts without secret key", %{context: context} do
+      {_, %DecryptionResult{session_key: session_key}} =
+        Context.decrypt_export_session_key!(context, @encrypted_receiver)
+
+      path = "priv/test/keychains/#{:erlang.crc32("#{inspect make_ref()}")}"
+      File.mkdir!(path)
+      File.chmod!(path, 0o700)
+      on_exit fn -> File.rm_rf!(path) end
+
+      empty_context = Context.from_protocol!(:open_pgp)
+      Context.set_engine_home_dir!(empty_context, path)
+
+      assert {:error, _} = Context.decrypt(empty_context, @encrypted_receiver)
+      assert {:ok, "Hello World!"} = Context.decrypt_with_session_key(empty_context, session_key, @encrypted_receiver)
+    end
+
+    @tag context: true, import_receiver_secret: true
+    test "keeps a previously set override-session-key flag", %{context: context} do
+      {_, %DecryptionResult{session_key: session_key}} =
+        Context.decrypt_export_session_key!(context, @encrypted_receiver)
+      :ok = Context.set_flag(context, "override-session-key", session_key)
+
+      assert {:ok, "Hello World!"} = Context.decrypt_with_session_key(context, session_key, @encrypted_receiver)
+      assert {:ok, ^session_key} = Context.get_flag(context, "override-session-key")
+    end
+  end
+
   describe "armor?/1" do
     @tag context: true
     test "read correctly", %{context: context} do
@@ -277,7 +959,251 @@ defmodule ExGpgme.ContextTest do
       assert {:ok, signature} = Context.sign(context, "Hello World")
       assert verification = Context.verify_opaque!(context, signature, "Hello World")
       assert %VerificationResult{signatures: [signature_result]} = verification
-      assert %Signature{status: :valid} = signature_result
+      assert %Signature{status: :valid, issuer_serial: nil, issuer_name: nil, chain_id: nil} = signature_result
+    end
+
+    test "creates CMS signature with X.509 details" do
+      context = cms_context!()
+
+      assert {:ok, signature} = Context.sign(context, "Hello World")
+      assert signature =~ "-BEGIN SIGNED MESSAGE-"
+
+      assert %VerificationResult{signatures: [signature_result]} =
+        Context.verify_opaque!(context, signature, "Hello World")
+      assert %Signature{status: :valid, fingerprint: @x509_fingerprint, issuer_serial: @x509_issuer_serial,
+        issuer_name: @x509_issuer_name, chain_id: @x509_fingerprint} = signature_result
+    end
+
+    test "includes certificates in CMS signature" do
+      context = cms_context!()
+
+      Context.set_include_certs(context, 0)
+      without_certs = Context.sign!(context, "Hello World")
+      Context.set_include_certs(context, 1)
+      with_certs = Context.sign!(context, "Hello World")
+
+      assert byte_size(with_certs) > byte_size(without_certs)
+      assert %VerificationResult{signatures: [%Signature{status: :valid}]} =
+        Context.verify_opaque!(context, with_certs, "Hello World")
+    end
+  end
+
+  describe "verify_opaque_with_audit_log/4" do
+    test "gives log of CMS verification" do
+      context = cms_context!()
+      signature = Context.sign!(context, "Hello World")
+
+      assert {:ok, {%VerificationResult{signatures: [%Signature{status: :valid}]}, text_log}} =
+        Context.verify_opaque_with_audit_log(context, signature, "Hello World", :text)
+      assert text_log =~ "Data verification succeeded"
+      assert text_log =~ @x509_issuer_serial
+
+      assert {:ok, {%VerificationResult{}, html_log}} =
+        Context.verify_opaque_with_audit_log(context, signature, "Hello World", :html)
+      assert html_log =~ "<div"
+    end
+
+    test "gives the log of each caller with concurrent callers on a pool" do
+      context = cms_context!(pool_size: 3)
+      signature = Context.sign!(context, "Hello World")
+
+      1..6
+      |> Enum.map(fn _ ->
+        Task.async(fn -> Context.verify_opaque_with_audit_log!(context, signature, "Hello World", :text) end)
+      end)
+      |> Enum.map(&Task.await(&1, 30_000))
+      |> Enum.each(fn {_result, log} ->
+        assert log =~ "Data verification succeeded"
+      end)
+    end
+
+    test "gives argument error on unknown format" do
+      context = Context.from_protocol!(:cms)
+
+      assert_raise ArgumentError, fn ->
+        Context.verify_opaque_with_audit_log(context, "signature", "Hello World", :pdf)
+      end
+    end
+  end
+
+  describe "decrypt_with_audit_log/3" do
+    test "gives log of CMS decryption" do
+      context = cms_context!()
+      recipient = Context.find_key!(context, @x509_fingerprint)
+      cyphertext = Context.encrypt!(context, [recipient], "Hello World!")
+
+      assert {:ok, {"Hello World!", log}} = Context.decrypt_with_audit_log(context, cyphertext, :text)
+      assert log =~ "Data decryption succeeded"
+    end
+
+    test "gives log of failed CMS decryption" do
+      context = cms_context!()
+      recipient = Context.find_key!(context, @x509_fingerprint)
+      cyphertext = Context.encrypt!(context, [recipient], "Hello World!")
+
+      assert {:error, {_error, log}} = Context.decrypt_with_audit_log(Context.ephemeral!(:cms), cyphertext, :text)
+      assert log =~ ~r/Data decryption succeeded:\s+No/
+    end
+  end
+
+  describe "audit_log/2" do
+    test "gives log of the last operation of the process" do
+      context = cms_context!()
+      recipient = Context.find_key!(context, @x509_fingerprint)
+      cyphertext = Context.encrypt!(context, [recipient], "Hello World!")
+      other = Context.ephemeral!(:cms)
+
+      assert {:error, _error} = Context.decrypt(other, cyphertext)
+      assert {:ok, log} = Context.audit_log(other, :text)
+      assert log =~ ~r/Data decryption succeeded:\s+No/
+    end
+
+    test "gives no log after an operation of another process" do
+      context = cms_context!()
+      signature = Context.sign!(context, "Hello World")
+
+      assert {:ok, _result} = Context.verify_opaque(context, signature, "Hello World")
+      Task.await(Task.async(fn -> Context.verify_opaque!(context, signature, "Hello World") end), 30_000)
+
+      assert {:error, _error} = Context.audit_log(context, :text)
+    end
+
+    test "gives no log without operation" do
+      assert {:error, _error} = Context.audit_log(Context.from_protocol!(:cms), :text)
+    end
+  end
+
+  describe "create_certificate_request/2" do
+    test "creates PEM request with armor" do
+      context = Context.ephemeral!(:cms, armor: true)
+
+      assert {:ok, request} = Context.create_certificate_request(context, subject: "CN=Alice,O=Example",
+        emails: ["alice@example.com"], dns_names: ["example.com"], passphrase: "passphrase")
+      assert request =~ "-----BEGIN CERTIFICATE REQUEST-----"
+      assert request =~ "-----END CERTIFICATE REQUEST-----"
+    end
+
+    test "creates DER request without armor" do
+      context = Context.ephemeral!(:cms)
+
+      assert {:ok, <<0x30, _ :: binary>>} = Context.create_certificate_request(context, subject: "CN=Alice",
+        key_usage: [:sign], passphrase: "passphrase")
+    end
+
+    test "errors without subject" do
+      context = Context.ephemeral!(:cms)
+
+      assert {:error, "Missing option subject"} = Context.create_certificate_request(context, emails: [])
+    end
+
+    test "errors on line breaks in parameters" do
+      context = Context.ephemeral!(:cms)
+
+      assert {:error, "Certificate request parameters must not contain line breaks"} =
+        Context.create_certificate_request(context, subject: "CN=Alice\nKey-Length: 512")
+    end
+  end
+
+  describe "include_certs/1" do
+    test "defaults to engine default" do
+      assert -256 = Context.include_certs(Context.from_protocol!(:cms))
+    end
+  end
+
+  describe "set_include_certs/2" do
+    test "sets on all contexts of the pool" do
+      context = Context.from_protocol!(:cms, pool_size: 2)
+
+      assert :ok = Context.set_include_certs(context, -2)
+      assert %{include_certs: -2} = Context.settings!(context)
+      assert %{include_certs: -2} = Context.settings!(Context.clone!(context))
+    end
+  end
+
+  describe "add_signature_notation/4" do
+    @tag context: true, import_receiver_secret: true, armor: true
+    test "adds notation to signatures", %{context: context} do
+      assert :ok = Context.add_signature_notation(context, "build@example.com", "1.2.3")
+      assert {:ok, [%SignatureNotation{name: "build@example.com", value: "1.2.3"}]} =
+        Context.signature_notations(context)
+
+      signature = Context.sign!(context, "Hello World")
+      assert %VerificationResult{signatures: [%Signature{notations: notations}]} =
+        Context.verify_opaque!(context, signature, "Hello World")
+      assert [%SignatureNotation{name: "build@example.com", value: "1.2.3", is_human_readable: true}] = notations
+    end
+
+    @tag context: true
+    test "gives argument error on wrong flags", %{context: context} do
+      assert_raise ArgumentError, fn ->
+        Context.add_signature_notation(context, "build@example.com", "1.2.3", [:foo])
+      end
+    end
+  end
+
+  describe "clear_signature_notations/1" do
+    @tag context: true
+    test "clears notations and policy url", %{context: context} do
+      Context.add_signature_notation!(context, "build@example.com", "1.2.3")
+      Context.set_signature_policy_url!(context, "https://example.com/policy")
+
+      assert :ok = Context.clear_signature_notations(context)
+      assert {:ok, []} = Context.signature_notations(context)
+      assert nil == Context.signature_policy_url(context)
+    end
+  end
+
+  describe "set_signature_policy_url/3" do
+    @tag context: true, import_receiver_secret: true, armor: true
+    test "adds policy url to signatures", %{context: context} do
+      assert :ok = Context.set_signature_policy_url(context, "https://example.com/policy")
+      assert "https://example.com/policy" = Context.signature_policy_url(context)
+
+      signature = Context.sign!(context, "Hello World")
+      assert %VerificationResult{signatures: [%Signature{policy_url: "https://example.com/policy"}]} =
+        Context.verify_opaque!(context, signature, "Hello World")
+    end
+  end
+
+  describe "set_sender/2" do
+    @tag context: true
+    test "set correctly", %{context: context} do
+      assert nil == Context.sender(context)
+
+      assert :ok = Context.set_sender(context, "alice@example.com")
+      assert "alice@example.com" = Context.sender(context)
+
+      assert :ok = Context.set_sender(context, nil)
+      assert nil == Context.sender(context)
+    end
+
+    @tag context: true, import_receiver_secret: true
+    test "signs with sender", %{context: context} do
+      key = Context.find_key!(context, @receiver_fingerprint)
+      %KeyDetails{user_ids: [%UserId{email: email} | _]} = Context.key_details!(key)
+
+      assert nil == signers_uid(Context.sign!(context, :clear, "Hello World"))
+
+      Context.set_sender!(context, email)
+
+      signature = Context.sign!(context, :clear, "Hello World")
+      assert email == signers_uid(signature)
+      assert %VerificationResult{signatures: [%Signature{status: :valid, fingerprint: @receiver_fingerprint}]} =
+        Context.verify_opaque!(context, signature, "Hello World")
+    end
+
+    @tag context: true, import_receiver_secret: true
+    test "signs with sender not matching the signing key", %{context: context} do
+      key = Context.find_key!(context, @receiver_fingerprint)
+      %KeyDetails{user_ids: user_ids} = Context.key_details!(key)
+      refute Enum.any?(user_ids, &match?(%UserId{email: "other@example.org"}, &1))
+
+      Context.set_sender!(context, "other@example.org")
+
+      signature = Context.sign!(context, :clear, "Hello World")
+      assert "other@example.org" == signers_uid(signature)
+      assert %VerificationResult{signatures: [%Signature{status: :valid, fingerprint: @receiver_fingerprint}]} =
+        Context.verify_opaque!(context, signature, "Hello World")
     end
   end
 end
@@ -0,0 +1,59 @@
+defmodule ExGpgme.EngineTest do
+  @moduledoc false
+
+  use ExUnit.Case
+  alias ExGpgme.{Context, Engine}
+  alias ExGpgme.Engine.EngineInfo
+
+  doctest Engine, except: [
+    version: 0,
+    engine_infos: 0,
+    check_version: 2,
+  ]
+
+  describe "version/0" do
+    test "gives library version" do
+      assert Engine.version =~ ~r/^\d+\.\d+/
+    end
+  end
+
+  describe "engine_infos/0" do
+    test "lists every protocol" do
+      assert {:ok, engine_infos} = Engine.engine_infos
+
+      protocols = Enum.map(engine_infos, fn %EngineInfo{protocol: protocol} -> protocol end)
+      for protocol <- [:open_pgp, :cms, :gpg_conf, :assuan, :g13, :ui_server, :spawn] do
+        assert protocol in protocols
+      end
+    end
+  end
+
+  describe "check_version/2" do
+    test "accepts installed engine" do
+      assert :ok = Engine.check_version(:open_pgp, "1.0")
+    end
+
+    test "rejects old engine" do
+      %EngineInfo{version: version} = Enum.find(Engine.engine_infos!, &(&1.protocol == :open_pgp))
+
+      assert {:error, message} = Engine.check_version(:open_pgp, "999.0.0")
+      assert message == "Engine :open_pgp version #{version} is older than required version 999.0.0"
+    end
+
+    test "rejects invalid version" do
+      assert {:error, "Invalid version \"latest\""} = Engine.check_version(:open_pgp, "latest")
+    end
+
+    test "compares versions numerically like the capabilities" do
+      assert {:ok, :lt} = Context.compare_versions("2.2.9", "2.2.10")
+      assert {:ok, :eq} = Context.compare_versions("2.1", "2.1.0")
+      assert {:ok, :gt} = Context.compare_versions("2.2.0-beta123", "2.1.99")
+    end
+
+    test "raises for unknown protocol" do
+      assert_raise RuntimeError, "Engine :default is not installed", fn ->
+        Engine.check_version!(:default, "1.0.0")
+      end
+    end
+  end
+end
@@ -0,0 +1,132 @@
+defmodule ExGpgme.GpgConfTest do
+  @moduledoc false
+
+  use ExUnit.Case
+  alias ExGpgme.{Context, GpgConf}
+  alias ExGpgme.GpgConf.{Component, ComponentOption}
+
+  doctest GpgConf, except: [
+    components: 1,
+    component: 2,
+    option: 3,
+    change_option: 4,
+    launch: 2,
+    reload: 2,
+    kill: 2,
+  ]
+
+  setup do
+    dirname = :erlang.crc32("#{inspect make_ref()}")
+    path = "priv/test/keychains/#{dirname}"
+
+    File.mkdir!(path)
+    File.chmod!(path, 0o700)
+
+    on_exit fn ->
+      File.rm_rf!(path)
+    end
+
+    context = Context.from_protocol!(:open_pgp)
+    Context.set_engine_home_dir!(context, path)
+
+    {:ok, %{context: context, path: path}}
+  end
+
+  describe "components/1" do
+    test "lists components with options", %{context: context} do
+      assert {:ok, components} = GpgConf.components(context)
+
+      names = Enum.map(components, fn %Component{name: name} -> name end)
+      for name <- ["gpg", "gpg-agent", "dirmngr"] do
+        assert name in names
+      end
+
+      assert %Component{options: [%ComponentOption{} | _]} = Enum.find(components, &(&1.name == "gpg-agent"))
+    end
+  end
+
+  describe "component/2" do
+    test "errors with unknown component", %{context: context} do
+      assert {:error, "Unknown component foo"} = GpgConf.component(context, "foo")
+    end
+  end
+
+  describe "option/3" do
+    test "gives option details", %{context: context} do
+      assert {:ok, %ComponentOption{type: :uint32, value: nil, default: default}} =
+        GpgConf.option(context, "gpg-agent", "default-cache-ttl")
+
+      assert is_binary(default)
+    end
+  end
+
+  describe "change_option/4" do
+    test "writes numbers", %{context: context, path: path} do
+      assert :ok = GpgConf.change_option(context, "gpg-agent", "default-cache-ttl", 300)
+
+      assert {:ok, %ComponentOption{value: "300"}} = GpgConf.option(context, "gpg-agent", "default-cache-ttl")
+      assert File.read!(Path.join(path, "gpg-agent.conf")) =~ "default-cache-ttl 300"
+
+      assert :ok = GpgConf.change_option(context, "gpg-agent", "default-cache-ttl", nil)
+      assert {:ok, %ComponentOption{value: nil}} = GpgConf.option(context, "gpg-agent", "default-cache-ttl")
+    end
+
+    test "writes strings", %{context: context} do
+      assert :ok = GpgConf.change_option(context, "dirmngr", "keyserver", "hkp://127.0.0.1:11371")
+
+      assert {:ok, %ComponentOption{value: "hkp://127.0.0.1:11371"}} = GpgConf.option(context, "dirmngr", "keyserver")
+    end
+
+    test "errors with unknown option", %{context: context} do
+      assert {:error, _error} = GpgConf.change_option(context, "gpg-agent", "foo", 1)
+    end
+  end
+
+  defp agent_running?(path) do
+    {output, _status} = System.cmd("gpg-connect-agent", ["--homedir", path, "--no-autostart", "/bye"],
+      stderr_to_stdout: true)
+
+    not (output =~ "no gpg-agent running")
+  end
+
+  defp wait_for_agent_exit(path, attempts \\ 50)
+  defp wait_for_agent_exit(_path, 0), do: false
+  defp wait_for_agent_exit(path, attempts) do
+    :erlang.garbage_collect()
+    if agent_running?(path) do
+      Process.sleep(100)
+      wait_for_agent_exit(path, attempts - 1)
+    else
+      true
+    end
+  end
+
+  describe "launch/2 and kill/2" do
+    test "start and stop the agent", %{context: context, path: path} do
+      assert :ok = GpgConf.launch(context, "gpg-agent")
+      assert agent_running?(path)
+
+      assert :ok = GpgConf.reload(context, "gpg-agent")
+      assert agent_running?(path)
+
+      assert :ok = GpgConf.kill(context, "gpg-agent")
+      assert wait_for_agent_exit(path)
+    end
+
+    test "errors with unknown component", %{context: context} do
+      assert {:error, _error} = GpgConf.launch(context, "foo")
+    end
+  end
+
+  describe "ExGpgme.Context.set_kill_on_drop/2" do
+    test "kills the agent when the context is garbage collected", %{path: path} do
+      task = Task.async(fn ->
+        context = Context.from_protocol!(:open_pgp, engine_home_dir: path, kill_on_drop: true)
+        :ok = GpgConf.launch(context, "gpg-agent")
+      end)
+      Task.await(task)
+
+      assert wait_for_agent_exit(path)
+    end
+  end
+end
@@ -0,0 +1,76 @@
+defmodule ExGpgme.WkdTest do
+  @moduledoc false
+
+  use ExUnit.Case
+  alias ExGpgme.{Context, Wkd}
+  alias ExGpgme.Keys.{KeyDetails, UserId}
+
+  doctest Wkd, except: [
+    generate: 4,
+  ]
+
+  @sender_fingerprint "95E93F470BCB2E96C648572DFBFA85913EE05E95"
+  @sender_public_key File.read!("priv/test/keys/sender_public.asc")
+
+  @multi_uid_fingerprint "AC5DBFC6D8E16240FF7350179E64057AB26E584D"
+  @multi_uid_public_key File.read!("priv/test/keys/multi_uid_public.asc")
+
+  setup do
+    dirname = :erlang.crc32("#{inspect make_ref()}")
+    path = "priv/test/keychains/#{dirname}"
+
+    File.mkdir!(path)
+    File.chmod!(path, 0o700)
+
+    on_exit fn ->
+      File.rm_rf!(path)
+    end
+
+    context = Context.from_protocol!(:open_pgp)
+    Context.set_engine_home_dir!(context, path)
+    Context.import!(context, @sender_public_key)
+
+    {:ok, %{context: context, path: path}}
+  end
+
+  describe "generate/4" do
+    test "writes a directory for the keys of the domain", %{context: context, path: path} do
+      key = Context.find_key!(context, @sender_fingerprint)
+      %KeyDetails{user_ids: [%UserId{email: email} | _]} = Context.key_details!(key)
+      [local_part, domain] = String.split(email, "@")
+      directory = Path.join(path, "wkd")
+
+      assert {:ok, [key_path]} = Wkd.generate(context, [key], domain, directory)
+      assert key_path == Path.join([directory, "hu", Wkd.hash_local_part(local_part)])
+      assert File.exists?(Path.join(directory, "policy"))
+      refute Context.armor?(context)
+
+      assert {:ok, [%KeyDetails{fingerprint: @sender_fingerprint}]} =
+        Context.inspect_keys(context, File.read!(key_path))
+    end
+
+    test "only keeps the user ID of the mailbox", %{context: context, path: path} do
+      Context.import!(context, @multi_uid_public_key)
+      key = Context.find_key!(context, @multi_uid_fingerprint)
+
+      assert {:ok, [key_path]} = Wkd.generate(context, [key], "example.org", Path.join(path, "wkd"))
+      assert {:ok, [%KeyDetails{fingerprint: @multi_uid_fingerprint, user_ids: [%UserId{email: "multi@example.org"}]}]} =
+        Context.inspect_keys(context, File.read!(key_path))
+    end
+
+    test "does not change the context", %{context: context, path: path} do
+      Context.set_armor(context, true)
+      key = Context.find_key!(context, @sender_fingerprint)
+
+      assert {:ok, [key_path]} = Wkd.generate(context, [key], "example.com", Path.join(path, "wkd"))
+      assert Context.armor?(context)
+      refute File.read!(key_path) =~ "-BEGIN PGP PUBLIC KEY BLOCK-"
+    end
+
+    test "skips keys of other domains", %{context: context, path: path} do
+      key = Context.find_key!(context, @sender_fingerprint)
+
+      assert {:ok, []} = Wkd.generate(context, [key], "not-existing.invalid", Path.join(path, "wkd"))
+    end
+  end
+end
@@ -0,0 +1,92 @@
+defmodule ExGpgme.Test.HkpStub do
+  @moduledoc false
+
+  # Minimal HKP keyserver to run keyserver operations against in tests.
+
+  alias ExGpgme.Context
+  alias ExGpgme.Keys.{KeyDetails, Subkey, UserId}
+
+  @algorithms %{rsa: 1, rsa_encrypt: 2, rsa_sign: 3, elgamal_encrypt: 16, dsa: 17, ecdh: 18, ecdsa: 19, eddsa: 22}
+
+  def start(armored_keys \\ []) do
+    {:ok, store} = Agent.start_link(fn -> %{keys: armored_keys, uploads: []} end)
+    {:ok, listen_socket} = :gen_tcp.listen(0, [:binary, packet: :http_bin, active: false, reuseaddr: true,
+      ip: {127, 0, 0, 1}])
+    {:ok, port} = :inet.port(listen_socket)
+
+    spawn_link(fn -> accept(listen_socket, store) end)
+
+    {:ok, %{url: "hkp://127.0.0.1:#{port}", store: store}}
+  end
+
+  def url(%{url: url}), do: url
+
+  def uploads(%{store: store}), do: Agent.get(store, &(&1.uploads))
+
+  defp accept(listen_socket, store) do
+    {:ok, socket} = :gen_tcp.accept(listen_socket)
+    serve(socket, store)
+    accept(listen_socket, store)
+  end
+
+  defp serve(socket, store) do
+    {:ok, {:http_request, method, {:abs_path, path}, _version}} = :gen_tcp.recv(socket, 0)
+    headers = read_headers(socket, %{})
+
+    {status, body} = case {method, URI.parse(path)} do
+      {:GET, %URI{path: "/pks/lookup", query: query}} ->
+        lookup(store, URI.decode_query(query || ""))
+      {:POST, %URI{path: "/pks/add"}} ->
+        :ok = :inet.setopts(socket, packet: :raw)
+        {:ok, request_body} = :gen_tcp.recv(socket, String.to_integer(Map.get(headers, "content-length", "0")))
+        %{"keytext" => keytext} = URI.decode_query(request_body)
+        Agent.update(store, fn state -> %{state | uploads: [keytext | state.uploads]} end)
+        {200, ""}
+      _ ->
+        {404, ""}
+    end
+
+    :gen_tcp.send(socket, "HTTP/1.0 #{status} Stub\r\nContent-Length: #{byte_size(body)}\r\n\r\n" <> body)
+    :gen_tcp.close(socket)
+  end
+
+  defp read_headers(socket, headers) do
+    case :gen_tcp.recv(socket, 0) do
+      {:ok, {:http_header, _, name, _, value}} ->
+        read_headers(socket, Map.put(headers, String.downcase(to_string(name)), value))
+      {:ok, :http_eoh} ->
+        headers
+    end
+  end
+
+  defp lookup(store, %{"op" => op, "search" => search}) do
+    search = search |> String.replace_prefix("0x", "") |> String.upcase
+
+    matches = store
+    |> Agent.get(&(&1.keys))
+    |> Enum.flat_map(fn armored_key ->
+      {:ok, details} = Context.inspect_keys(Context.from_protocol!(:open_pgp), armored_key)
+      Enum.map(details, &({&1, armored_key}))
+    end)
+    |> Enum.filter(fn {%KeyDetails{fingerprint: fingerprint}, _} -> String.ends_with?(fingerprint, search) end)
+
+    case {op, matches} do
+      {_, []} -> {404, ""}
+      {"get", matches} -> {200, matches |> Enum.map(&elem(&1, 1)) |> Enum.join("\n")}
+      {"index", matches} -> {200, index(Enum.map(matches, &elem(&1, 0)))}
+      _ -> {501, ""}
+    end
+  end
+  defp lookup(_store, _query), do: {400, ""}
+
+  defp index(key_details) do
+    lines = Enum.flat_map(key_details, fn %KeyDetails{fingerprint: fingerprint, subkeys: [primary | _]} = key ->
+      %Subkey{key_algorithm: algorithm, length: length, creation_time: created} = primary
+      pub = "pub:#{fingerprint}:#{Map.get(@algorithms, algorithm, 0)}:#{length}:#{created}::"
+      uids = Enum.map(key.user_ids, fn %UserId{id: id} -> "uid:#{URI.encode(id)}:::" end)
+      [pub | uids]
+    end)
+
+    Enum.join(["info:1:#{Enum.count(key_details)}" | lines], "\n") <> "\n"
+  end
+end
@@ -0,0 +1,103 @@
+defmodule ExGpgme.Test.WkdStub do
+  @moduledoc false
+
+  # HTTP proxy standing in for the HTTPS servers of Web Key Directories in tests. It records the requested hosts,
+  # terminates the tunnel with the certificate of `example.com` and `openpgpkey.example.com` issued by
+  # `priv/test/keys/wkd_ca.pem` and serves the Web Key Directory written to `directory` by `ExGpgme.Wkd.generate/4`.
+  #
+  # `dirmngr` only trusts the system certificate authorities for Web Key Directories. Neither `hkp-cacert` nor the
+  # `trusted-certs` of its configuration are used, so lookups only succeed where `wkd_ca.pem` is installed as a system
+  # certificate authority. Tests relying on that are tagged `:wkd_ca`.
+
+  @certfile "priv/test/keys/wkd_server.pem"
+  @keyfile "priv/test/keys/wkd_server.key"
+
+  def start(directory \\ nil) do
+    {:ok, _} = Application.ensure_all_started(:ssl)
+    {:ok, store} = Agent.start_link(fn -> %{hosts: [], paths: []} end)
+    {:ok, listen_socket} = :gen_tcp.listen(0, [:binary, packet: :http_bin, active: false, reuseaddr: true,
+      ip: {127, 0, 0, 1}])
+    {:ok, port} = :inet.port(listen_socket)
+
+    spawn_link(fn -> accept(listen_socket, directory, store) end)
+
+    {:ok, %{url: "http://127.0.0.1:#{port}", store: store}}
+  end
+
+  def url(%{url: url}), do: url
+
+  def dirmngr_conf(stub), do: "http-proxy #{url(stub)}\n"
+
+  def hosts(%{store: store}), do: Agent.get(store, &Enum.reverse(&1.hosts))
+
+  def paths(%{store: store}), do: Agent.get(store, &Enum.reverse(&1.paths))
+
+  defp accept(listen_socket, directory, store) do
+    {:ok, socket} = :gen_tcp.accept(listen_socket)
+    spawn(fn -> tunnel(socket, directory, store) end)
+    accept(listen_socket, directory, store)
+  end
+
+  defp tunnel(socket, directory, store) do
+    case :gen_tcp.recv(socket, 0) do
+      {:ok, {:http_request, "CONNECT", {:scheme, host, port}, _version}} ->
+        Agent.update(store, &%{&1 | hosts: ["#{host}:#{port}" | &1.hosts]})
+        :ok = skip_headers(&:gen_tcp.recv(socket, 0))
+        :ok = :inet.setopts(socket, packet: :raw)
+        :ok = :gen_tcp.send(socket, "HTTP/1.0 200 Connection established\r\n\r\n")
+        serve(socket, directory, store)
+      _ ->
+        :gen_tcp.send(socket, "HTTP/1.0 405 Stub\r\nContent-Length: 0\r\n\r\n")
+    end
+
+    :gen_tcp.close(socket)
+  end
+
+  defp serve(socket, directory, store) do
+    with {:ok, ssl_socket} <- :ssl.ssl_accept(socket, [certfile: @certfile, keyfile: @keyfile], 5_000) do
+      :ok = :ssl.setopts(ssl_socket, packet: :http_bin)
+
+      case :ssl.recv(ssl_socket, 0) do
+        {:ok, {:http_request, :GET, {:abs_path, path}, _version}} ->
+          :ok = skip_headers(&:ssl.recv(ssl_socket, 0))
+          path = path |> String.split("?") |> hd
+          Agent.update(store, &%{&1 | paths: [path | &1.paths]})
+          :ssl.send(ssl_socket, response(directory, path))
+        _ ->
+          :ok
+      end
+
+      :ssl.close(ssl_socket)
+    end
+  end
+
+  defp skip_headers(recv) do
+    case recv.() do
+      {:ok, :http_eoh} -> :ok
+      {:ok, {:http_header, _, _, _, _}} -> skip_headers(recv)
+      {:error, error} -> {:error, error}
+    end
+  end
+
+  defp response(directory, path) do
+    with true <- is_binary(directory),
+         {:ok, relative_path} <- directory_path(path),
+         {:ok, content} <- File.read(Path.join(directory, relative_path)) do
+      ["HTTP/1.0 200 OK\r\nContent-Type: application/octet-stream\r\nContent-Length: #{byte_size(content)}\r\n\r\n",
+        content]
+    else
+      _ -> "HTTP/1.0 404 Not Found\r\nContent-Length: 0\r\n\r\n"
+    end
+  end
+
+  defp directory_path("/.well-known/openpgpkey/" <> relative_path) do
+    case String.split(relative_path, "/") do
+      ["policy"] -> {:ok, "policy"}
+      ["hu", hash] -> {:ok, Path.join("hu", hash)}
+      [_domain, "policy"] -> {:ok, "policy"}
+      [_domain, "hu", hash] -> {:ok, Path.join("hu", hash)}
+      _ -> :error
+    end
+  end
+  defp directory_path(_path), do: :error
+end
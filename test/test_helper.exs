@@ -1,2 +1,6 @@
-ExUnit.configure exclude: [:manual_pinentry]
+Code.require_file("support/hkp_stub.exs", __DIR__)
+Code.require_file("support/wkd_stub.exs", __DIR__)
+
+# `:wkd_ca` tests need `priv/test/keys/wkd_ca.pem` installed as a system certificate authority, see `WkdStub`.
+ExUnit.configure exclude: [:manual_pinentry, :wkd_ca]
 ExUnit.start()